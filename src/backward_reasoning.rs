@@ -0,0 +1,125 @@
+// backward reasoning: starting from every halting transition the possible predecessor configurations are searched;
+// a partial configuration only knows the cells the backward run has read, other cells may be anything
+// if every branch dies before `depth` without passing through a configuration compatible with the initial one,
+// no configuration reachable from the start halts
+
+use crate::{
+    ProverResult,
+    machine::{Machine, Transition},
+    pipeline,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// max number of backward steps
+    pub depth: usize,
+    /// max number of visited partial configurations
+    pub nodes: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { depth: 100, nodes: 100_000 }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Partial {
+    state: u8,
+    /// `None` == unknown
+    tape: Vec<Option<u8>>,
+    head: usize,
+    depth: usize,
+}
+
+impl Partial {
+    /// configuration which could be the blank tape in state A
+    fn is_initial(&self) -> bool {
+        self.state == 0 && self.tape.iter().all(|&cell| cell.unwrap_or(0) == 0)
+    }
+
+    /// configuration before `trans` was executed in `state` reading `symbol`
+    fn predecessor(&self, state: u8, symbol: u8, trans: Transition) -> Option<Partial> {
+        let mut prev = Partial { state, depth: self.depth + 1, ..self.clone() };
+        if trans.head.orient == 0 {
+            if prev.head + 1 == prev.tape.len() {
+                prev.tape.push(None);
+            }
+            prev.head += 1;
+        } else if prev.head == 0 {
+            prev.tape.insert(0, None);
+        } else {
+            prev.head -= 1;
+        }
+        let cell = &mut prev.tape[prev.head];
+        if matches!(*cell, Some(s) if s != trans.symbol) {
+            return None;
+        }
+        *cell = Some(symbol);
+        Some(prev)
+    }
+}
+
+pub fn decide(machine: &Machine, config: &Config) -> ProverResult {
+    let states = machine.states();
+    let transitions = (0..states).flat_map(|state| (0..machine.symbols()).map(move |symbol| (state, symbol)));
+    let mut stack: Vec<Partial> = transitions
+        .clone()
+        .filter(|&(state, symbol)| machine.get_transition(symbol, state).filter(|t| t.head.state < states).is_none())
+        .map(|(state, symbol)| Partial { state, tape: vec![Some(symbol)], head: 0, depth: 0 })
+        .collect();
+    // defined non-halting transitions by their target state
+    let mut into = vec![vec![]; states as usize];
+    for (state, symbol) in transitions {
+        if let Some(trans) = machine.get_transition(symbol, state).filter(|t| t.head.state < states) {
+            into[trans.head.state as usize].push((state, symbol, trans));
+        }
+    }
+
+    let (mut nodes, mut max_depth) = (0, 0);
+    while let Some(partial) = stack.pop() {
+        if partial.is_initial() {
+            return ProverResult::Limit(format!("initial configuration reached at depth {}", partial.depth));
+        }
+        if partial.depth >= config.depth {
+            return ProverResult::Limit(format!("depth: {}", config.depth));
+        }
+        nodes += 1;
+        if nodes > config.nodes {
+            return ProverResult::Limit(format!("nodes: {}", config.nodes));
+        }
+        if pipeline::expired() {
+            return ProverResult::Limit("time".to_owned());
+        }
+        max_depth = max_depth.max(partial.depth);
+        let preds = into[partial.state as usize].iter().filter_map(|&(s, sym, t)| partial.predecessor(s, sym, t));
+        stack.extend(preds);
+    }
+    ProverResult::Infinite(format!("backward reasoning: depth {}", max_depth))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depth() {
+        for (machine, depth) in [("1RB0LA_1LA---", 2), ("1RB0LA_0RC1LA_1LA---", 7)] {
+            let result = decide(&Machine::from(machine), &Config::default());
+            assert_eq!(result.to_string(), format!("Infinite(\"backward reasoning: depth {}\")", depth), "{}", machine);
+        }
+    }
+
+    #[test]
+    fn depth_limit() {
+        let result = decide(&Machine::from("1RB0LA_0RC1LA_1LA---"), &Config { depth: 3, ..Config::default() });
+        assert_eq!(result.to_string(), "Limit(\"depth: 3\")");
+    }
+
+    #[test]
+    fn initial_configuration_reached() {
+        // halts in B on the blank right of the first written cell
+        let result = decide(&Machine::from("1RB1RB_---1LA"), &Config::default());
+        assert_eq!(result.to_string(), "Limit(\"initial configuration reached at depth 1\")");
+    }
+}
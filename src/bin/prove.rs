@@ -0,0 +1,141 @@
+use anyhow::{Context, Result, bail};
+use argh::FromArgs;
+use bbc::{
+    ProverResult,
+    index_set::IndexSet,
+    machine::Machine,
+    pipeline::{Pipeline, Stage},
+    seed_db::SeedDb,
+};
+use indexmap::IndexMap;
+use rayon::prelude::*;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    time::Instant,
+};
+
+/// prove machines en masse; writes `index<TAB>machine<TAB>result<TAB>stage` lines
+#[derive(FromArgs)]
+struct Args {
+    /// text file with one machine per line, optionally prefixed by its index (`-` == stdin)
+    #[argh(option)]
+    machines: Option<String>,
+    /// bbchallenge seed database
+    #[argh(option)]
+    db: Option<String>,
+    /// index file selecting machines from the seed database; repeatable (union)
+    #[argh(option)]
+    index: Vec<String>,
+    /// pipeline stage, e.g. `halt(100000)` or `ctl(4)@100ms`; repeatable, tried in order until one decides
+    #[argh(option, short = 'd')]
+    decider: Vec<Stage>,
+    /// whole pipeline in one string, e.g. `cycler(1000, 500) | halt(100000)`
+    #[argh(option)]
+    pipeline: Option<Pipeline>,
+    /// output file (default stdout)
+    #[argh(option, short = 'o')]
+    output: Option<String>,
+}
+
+fn result_kind(result: &ProverResult) -> &'static str {
+    match result {
+        ProverResult::Halt { .. } => "Halt",
+        ProverResult::Infinite(_) => "Infinite",
+        ProverResult::Limit(_) => "Limit",
+        ProverResult::Panic(_) => "Panic",
+    }
+}
+
+fn read_machines(path: &str) -> Result<Vec<(u32, Machine)>> {
+    let reader: Box<dyn BufRead> = if path == "-" {
+        Box::new(BufReader::new(std::io::stdin()))
+    } else {
+        Box::new(BufReader::new(File::open(path).with_context(|| format!("can't open {}", path))?))
+    };
+    let mut machines = Vec::new();
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('/') {
+            continue;
+        }
+        let (idx, machine) = match line.split_once(char::is_whitespace) {
+            Some((idx, machine)) if idx.bytes().all(|b| b.is_ascii_digit()) => (idx.parse()?, machine),
+            _ => (machines.len() as u32, line),
+        };
+        let machine = Machine::parse(machine).with_context(|| format!("{}:{}", path, lineno + 1))?;
+        machines.push((idx, machine));
+    }
+    Ok(machines)
+}
+
+const CHUNK: usize = 1 << 16;
+
+/// `i`-th input machine with its index
+type Source<'a> = Box<dyn Fn(usize) -> Result<(u32, Machine)> + Sync + 'a>;
+
+// cargo run --release --bin prove -- --db all_5_states_undecided_machines_with_global_header \
+//     --index bb5_undecided_index -d 'cycler(1000, 500)' -d 'halt(100000)' -o proofs.txt
+// echo 1RB1LB_1LA1RZ | cargo run --release --bin prove -- --machines -
+// cargo run --release --bin prove -- --machines holdouts.txt --pipeline 'cycler(1000, 500) | ctl(4)@100ms | halt(100000)'
+fn main() -> Result<()> {
+    let args: Args = argh::from_env();
+    let pipeline = match (args.pipeline, args.decider.is_empty()) {
+        (Some(_), false) => bail!("--pipeline & --decider can't be combined"),
+        (Some(pipeline), true) => pipeline,
+        (None, true) => Pipeline::default(),
+        (None, false) => Pipeline { stages: args.decider },
+    };
+    eprintln!("pipeline: {}", pipeline);
+    // panics are reported as `ProverResult::Panic`
+    std::panic::set_hook(Box::new(|_| {}));
+
+    let db;
+    let (count, get): (usize, Source) = match (&args.machines, &args.db) {
+        (Some(path), None) => {
+            let machines = read_machines(path)?;
+            (machines.len(), Box::new(move |i| Ok(machines[i].clone())))
+        }
+        (None, Some(path)) => {
+            db = SeedDb::open(path)?;
+            let db = &db;
+            if args.index.is_empty() {
+                (db.len() as usize, Box::new(move |i| Ok((i as u32, db.get(i as u32)?))))
+            } else {
+                let index = IndexSet::load_all(&args.index)?;
+                (index.len(), Box::new(move |i| Ok((index.as_slice()[i], db.get(index.as_slice()[i])?))))
+            }
+        }
+        _ => bail!("exactly one of --machines & --db is required"),
+    };
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path).with_context(|| format!("can't create {}", path))?)),
+        None => Box::new(BufWriter::new(std::io::stdout())),
+    };
+
+    let start = Instant::now();
+    let mut counts: IndexMap<&str, usize> = ["Halt", "Infinite", "Limit", "Panic"].iter().map(|&k| (k, 0)).collect();
+    for chunk in (0..count).step_by(CHUNK) {
+        let results = (chunk..count.min(chunk + CHUNK))
+            .into_par_iter()
+            .map(|i| {
+                let (idx, machine) = get(i)?;
+                let verdict = pipeline.prove(&machine);
+                Ok((idx, machine, verdict))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (idx, machine, verdict) in results {
+            *counts.get_mut(result_kind(&verdict.result)).unwrap() += 1;
+            let stage = verdict.stage.map(|stage| stage.to_string()).unwrap_or_default();
+            writeln!(out, "{}\t{}\t{}\t{}", idx, machine, verdict.result, stage)?;
+        }
+        out.flush()?;
+    }
+
+    let summary = counts.iter().map(|(kind, count)| format!("{}: {}", kind, count)).collect::<Vec<_>>().join(", ");
+    eprintln!("{} machines in {:.1?} ({})", count, start.elapsed(), summary);
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use anyhow::{Context, Result};
+use argh::FromArgs;
+use bbc::{
+    machine::{Head, Machine},
+    seed_db::SeedDb,
+    simulator::Simulator,
+};
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use hashbrown::HashMap;
+use std::io::Write;
+
+/// step through the simulation of one machine
+#[derive(FromArgs)]
+struct Args {
+    /// machine in any format `Machine::parse` accepts
+    #[argh(positional)]
+    machine: Option<String>,
+    /// bbchallenge seed database
+    #[argh(option)]
+    db: Option<String>,
+    /// machine index in the seed database
+    #[argh(option)]
+    idx: Option<u32>,
+}
+
+/// every `CHECKPOINT` steps simulator is saved so stepping backward doesn't need to start from scratch
+const CHECKPOINT: u64 = 1 << 10;
+/// how far to search for the next repeated configuration
+const REPEAT_LIMIT: u64 = 1 << 20;
+const PAGE: u64 = 100;
+
+struct Viewer<'a> {
+    sim: Simulator<'a>,
+    /// `checkpoints[i]` is the simulator after `i * CHECKPOINT` steps
+    checkpoints: Vec<Simulator<'a>>,
+    /// typed step number for goto
+    input: Option<String>,
+    message: String,
+}
+
+impl<'a> Viewer<'a> {
+    fn new(machine: &'a Machine) -> Viewer<'a> {
+        let sim = Simulator::new(machine);
+        Viewer { checkpoints: vec![sim.clone()], sim, input: None, message: String::new() }
+    }
+
+    fn forward(&mut self) -> bool {
+        if !self.sim.step() {
+            return false;
+        }
+        if self.sim.steps() == self.checkpoints.len() as u64 * CHECKPOINT {
+            self.checkpoints.push(self.sim.clone());
+        }
+        true
+    }
+
+    fn goto(&mut self, step: u64) {
+        if step < self.sim.steps() {
+            self.sim = self.checkpoints[(step / CHECKPOINT) as usize].clone();
+        }
+        while self.sim.steps() < step && self.forward() {}
+        if self.sim.steps() < step {
+            self.message = format!("halted after {} steps", self.sim.steps());
+        }
+    }
+
+    /// jumps to the first configuration after the current step which already occurred before
+    fn next_repeat(&mut self) {
+        let mut sim = Simulator::new(self.sim.machine());
+        let mut seen = HashMap::new();
+        let from = self.sim.steps();
+        loop {
+            let tape = sim.tape();
+            let start = tape.iter().position(|&s| s != 0).unwrap_or(tape.len());
+            let end = tape.iter().rposition(|&s| s != 0).map_or(start, |e| e + 1);
+            let offset = sim.position() - sim.head() as i64 + start as i64;
+            let config = (sim.state(), sim.position(), offset, tape[start..end].to_vec());
+            if let Some(prev) = seen.insert(config, sim.steps()) {
+                if sim.steps() > from {
+                    self.goto(sim.steps());
+                    self.message =
+                        format!("step {} repeats step {} (period {})", sim.steps(), prev, sim.steps() - prev);
+                    return;
+                }
+            }
+            if sim.steps() >= from + REPEAT_LIMIT || !sim.step() {
+                self.message = format!("no repeated configuration up to step {}", sim.steps());
+                return;
+            }
+        }
+    }
+
+    /// @returns false on quit
+    fn key(&mut self, key: KeyEvent) -> bool {
+        self.message.clear();
+        if let Some(input) = &mut self.input {
+            match key.code {
+                KeyCode::Char(c @ '0'..='9') => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let step = input.parse().unwrap_or(self.sim.steps());
+                    self.input = None;
+                    self.goto(step);
+                }
+                KeyCode::Esc => self.input = None,
+                _ => (),
+            }
+            return true;
+        }
+
+        let step = self.sim.steps();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => self.goto(step + 1),
+            KeyCode::Left | KeyCode::Char('h') => self.goto(step.saturating_sub(1)),
+            KeyCode::PageDown | KeyCode::Char('L') => self.goto(step + PAGE),
+            KeyCode::PageUp | KeyCode::Char('H') => self.goto(step.saturating_sub(PAGE)),
+            KeyCode::Home => self.goto(0),
+            KeyCode::Char('g') => self.input = Some(String::new()),
+            KeyCode::Char('r') => self.next_repeat(),
+            _ => (),
+        }
+        true
+    }
+
+    /// visible part of the tape; head is printed next to the cell it reads: `1 A> 0` or `1 <A 0`
+    fn tape(&self, width: usize) -> String {
+        let (tape, head) = (self.sim.tape(), self.sim.head());
+        let half = width.saturating_sub(4) / 4;
+        let (lo, hi) = (head.saturating_sub(half), (head + half + 1).min(tape.len()));
+        let head_str = Head { state: self.sim.state(), orient: self.sim.orient() }.to_string();
+        let mut cells: Vec<String> = tape[lo..hi].iter().map(|s| s.to_string()).collect();
+        let at = if self.sim.orient() == 1 { head - lo } else { head - lo + 1 };
+        cells.insert(at, head_str);
+        format!("{}{}{}", if lo > 0 { "… " } else { "" }, cells.join(" "), if hi < tape.len() { " …" } else { "" })
+    }
+
+    fn draw(&self, out: &mut impl Write) -> Result<()> {
+        let (width, _) = terminal::size()?;
+        let sim = &self.sim;
+        let next = match sim.halted() {
+            Some(_) => "halted".to_owned(),
+            None => match sim.machine().get_transition(sim.symbol(), sim.state()) {
+                Some(trans) => format!("next {}", trans),
+                None => "next ---".to_owned(),
+            },
+        };
+        let lines = [
+            format!("{}", sim.machine()),
+            format!(
+                "step {}  state {}  position {}  sigma {}  {}",
+                sim.steps(),
+                (sim.state() + b'A') as char,
+                sim.position(),
+                sim.sigma(),
+                next
+            ),
+            String::new(),
+            self.tape(width as usize),
+            String::new(),
+            "→/l/space step  ←/h back  PgDn/PgUp ±100  Home start  g goto  r next repeat  q quit".to_owned(),
+            match &self.input {
+                Some(input) => format!("goto step: {}", input),
+                None => self.message.clone(),
+            },
+        ];
+        queue!(out, Clear(ClearType::All))?;
+        for (y, line) in lines.iter().enumerate() {
+            queue!(out, MoveTo(0, y as u16))?;
+            if y == 3 {
+                queue!(out, SetAttribute(Attribute::Bold), Print(line), SetAttribute(Attribute::Reset))?;
+            } else {
+                queue!(out, Print(line))?;
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+fn run(machine: &Machine) -> Result<()> {
+    let mut viewer = Viewer::new(machine);
+    let mut out = std::io::stdout();
+    loop {
+        viewer.draw(&mut out)?;
+        if let Event::Key(key) = event::read()? {
+            if !viewer.key(key) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// cargo run --release --bin tui -- 1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA
+// cargo run --release --bin tui -- --db all_5_states_undecided_machines_with_global_header --idx 7410754
+fn main() -> Result<()> {
+    let args: Args = argh::from_env();
+    let machine = match (&args.machine, &args.db, args.idx) {
+        (Some(machine), None, None) => Machine::parse(machine)?,
+        (None, Some(db), Some(idx)) => SeedDb::open(db)?.get(idx)?,
+        _ => anyhow::bail!("either machine or --db with --idx is required"),
+    };
+
+    terminal::enable_raw_mode().context("raw mode")?;
+    execute!(std::io::stdout(), EnterAlternateScreen, Hide)?;
+    let ret = run(&machine);
+    execute!(std::io::stdout(), Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    ret
+}
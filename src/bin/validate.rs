@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use argh::FromArgs;
+use bbc::{ProverResult, index_set::IndexSet};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+/// compare `prove` output with other bbchallenge deciders' index files
+///
+/// exit code: 0 == consistent, 2 == contradictions, 3 == machines decided only by others (with --strict)
+#[derive(FromArgs)]
+struct Args {
+    /// output of `prove`
+    #[argh(positional)]
+    proofs: String,
+    /// index file of machines proven non-halting by other deciders; repeatable
+    #[argh(option)]
+    infinite: Vec<String>,
+    /// index file of machines proven halting by other deciders; repeatable
+    #[argh(option)]
+    halt: Vec<String>,
+    /// fail if other deciders decide machines we don't
+    #[argh(switch)]
+    strict: bool,
+    /// write `<prefix>only_ours`, `<prefix>only_theirs` & `<prefix>contradictions` index files
+    #[argh(option)]
+    write: Option<String>,
+}
+
+struct Proofs {
+    halt: IndexSet,
+    infinite: IndexSet,
+    undecided: IndexSet,
+}
+
+fn read_proofs(path: &str) -> Result<Proofs> {
+    let file = File::open(path).with_context(|| format!("can't open {}", path))?;
+    let (mut halt, mut infinite, mut undecided) = (Vec::new(), Vec::new(), Vec::new());
+    for (lineno, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        let parse = || -> Result<_> {
+            let mut it = line.split('\t');
+            let idx: u32 = it.next().context("missing index")?.parse()?;
+            let result: ProverResult = it.nth(1).context("missing result")?.parse()?;
+            Ok((idx, result))
+        };
+        let (idx, result) = parse().with_context(|| format!("{}:{}", path, lineno + 1))?;
+        match result {
+            ProverResult::Halt { .. } => halt.push(idx),
+            ProverResult::Infinite(_) => infinite.push(idx),
+            ProverResult::Limit(_) | ProverResult::Panic(_) => undecided.push(idx),
+        }
+    }
+    Ok(Proofs { halt: IndexSet::new(halt), infinite: IndexSet::new(infinite), undecided: IndexSet::new(undecided) })
+}
+
+// cargo run --release --bin validate -- proofs.txt --infinite bb5_decided_cyclers --infinite bb5_decided_tcyclers \
+//     --strict --write diff_
+fn main() -> Result<()> {
+    let args: Args = argh::from_env();
+
+    let ours = read_proofs(&args.proofs)?;
+    let their_halt = IndexSet::load_all(&args.halt)?;
+    let their_infinite = IndexSet::load_all(&args.infinite)?;
+
+    let decided = ours.halt.union(&ours.infinite);
+    let all = decided.union(&ours.undecided);
+    let their_decided = their_halt.union(&their_infinite);
+
+    let agreements = ours.halt.intersection(&their_halt).union(&ours.infinite.intersection(&their_infinite));
+    let contradictions = ours.halt.intersection(&their_infinite).union(&ours.infinite.intersection(&their_halt));
+    let only_ours = decided.difference(&their_decided);
+    let only_theirs = their_decided.intersection(&ours.undecided);
+    let not_covered = their_decided.difference(&all);
+
+    println!(
+        "machines:        {} ({} halt, {} infinite, {} undecided)",
+        all.len(),
+        ours.halt.len(),
+        ours.infinite.len(),
+        ours.undecided.len()
+    );
+    println!("agreements:      {}", agreements.len());
+    println!("only ours:       {}", only_ours.len());
+    println!("only theirs:     {}", only_theirs.len());
+    println!("contradictions:  {}", contradictions.len());
+    println!("not in proofs:   {}", not_covered.len());
+    contradictions.iter().take(20).for_each(|idx| {
+        let ours = if ours.halt.contains(idx) { "Halt" } else { "Infinite" };
+        println!("\t{} we say {}", idx, ours);
+    });
+
+    if let Some(prefix) = &args.write {
+        only_ours.save(format!("{}only_ours", prefix))?;
+        only_theirs.save(format!("{}only_theirs", prefix))?;
+        contradictions.save(format!("{}contradictions", prefix))?;
+    }
+
+    if !contradictions.is_empty() {
+        std::process::exit(2);
+    }
+    if args.strict && !only_theirs.is_empty() {
+        std::process::exit(3);
+    }
+    Ok(())
+}
@@ -0,0 +1,362 @@
+// bouncers: the tape grows linearly by repeating words, each sweep inserts one more copy of every word
+// records == steps on which the head visits a new rightmost cell; three records `i`, `j`, `k` in the same state whose
+// tapes grow by the same length give a formula tape `C(n)` (`C(0)` == record `i`, `C(1)` == `j`, `C(2)` == `k`)
+// with the inserted words repeated `n` times; then `C(n)` is simulated symbolically until it becomes `C(n + 1)`:
+// the head crosses a repeated word `w^k` by a shift rule (`q> w` -> `w' q>` without touching anything else, so
+// `q> w^k` -> `w'^k q>`) or by peeling one copy off; a bouncer growing only to the left is found as a right growing
+// bouncer of the mirrored machine & its formula is reported as `mirrored`
+
+use hashbrown::HashMap;
+use itertools::Itertools;
+use std::fmt;
+
+use crate::{
+    ProverResult,
+    machine::{Head, Machine},
+    simulator::{Limits, Simulator},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    pub limits: Limits,
+    /// max number of symbolic steps from `C(n)` to `C(n + 1)`
+    pub symbolic_steps: usize,
+    /// max number of steps of a shift rule
+    pub shift_steps: usize,
+    /// max number of record triples tried
+    pub attempts: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            limits: Limits { steps: 100_000, space: Some(1000) },
+            symbolic_steps: 100_000,
+            shift_steps: 1000,
+            attempts: 100,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Item {
+    Sym(u8),
+    /// `word^(n + c)`
+    Rep(Vec<u8>, u32),
+}
+
+/// tape sides are stored from the far end towards the head (words too), so both sides are handled the same way
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Formula {
+    sides: [Vec<Item>; 2],
+    head: Head,
+}
+
+impl fmt::Display for Formula {
+    /// `1 (011)^(n+2) 0 C>`; the right side is printed in tape order
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let item = |item: &Item, rev: bool| match item {
+            Item::Sym(s) => s.to_string(),
+            Item::Rep(w, c) => {
+                let mut w: Vec<String> = w.iter().map(|s| s.to_string()).collect();
+                if rev {
+                    w.reverse();
+                }
+                let exp = if *c == 0 { "n".to_owned() } else { format!("(n+{})", c) };
+                format!("({})^{}", w.concat(), exp)
+            }
+        };
+        let left = self.sides[0].iter().map(|i| item(i, false));
+        let right = self.sides[1].iter().rev().map(|i| item(i, true));
+        let items: Vec<String> = left.chain(std::iter::once(self.head.to_string())).chain(right).collect();
+        write!(f, "{}", items.join(" "))
+    }
+}
+
+/// one side in canonical form: blanks at the far end are dropped, repeated words are rotated towards the far end as
+/// much as possible & absorb the copies following them; both forms describe the same tape for every `n`
+fn canonical_side(side: &[Item]) -> Vec<Item> {
+    let mut ret: Vec<Item> = Vec::with_capacity(side.len());
+    for item in side {
+        match item.clone() {
+            Item::Sym(0) if ret.is_empty() => (),
+            Item::Sym(s) => {
+                ret.push(Item::Sym(s));
+                absorb(&mut ret);
+            }
+            Item::Rep(mut w, c) => {
+                // `s (w' s)^k` == `(s w')^k s`
+                let mut moved = vec![];
+                while let (Some(Item::Sym(s)), Some(&last)) = (ret.last(), w.last()) {
+                    if *s != last {
+                        break;
+                    }
+                    ret.pop();
+                    w.rotate_right(1);
+                    moved.push(last);
+                }
+                ret.push(Item::Rep(w, c));
+                for s in moved.into_iter().rev() {
+                    ret.push(Item::Sym(s));
+                    absorb(&mut ret);
+                }
+            }
+        }
+    }
+    ret
+}
+
+/// `w^(n + c) w` -> `w^(n + c + 1)` at the near end of `side`
+fn absorb(side: &mut Vec<Item>) {
+    let Some(pos) = side.iter().rposition(|item| matches!(item, Item::Rep(..))) else {
+        return;
+    };
+    let Item::Rep(w, _) = &side[pos] else { unreachable!() };
+    let tail = &side[pos + 1..];
+    if tail.len() == w.len() && tail.iter().zip(w).all(|(item, &s)| *item == Item::Sym(s)) {
+        side.truncate(pos + 1);
+        if let Item::Rep(_, c) = &mut side[pos] {
+            *c += 1;
+        }
+    }
+}
+
+impl Formula {
+    fn canonical(&self) -> Formula {
+        Formula { sides: [canonical_side(&self.sides[0]), canonical_side(&self.sides[1])], head: self.head }
+    }
+
+    /// `C(n + 1)`
+    fn next(&self) -> Formula {
+        let mut ret = self.clone();
+        ret.sides.iter_mut().flatten().for_each(|item| {
+            if let Item::Rep(_, c) = item {
+                *c += 1;
+            }
+        });
+        ret
+    }
+}
+
+fn expand(side: &[Item], n: u32) -> Vec<u8> {
+    let mut ret = vec![];
+    for item in side {
+        match item {
+            Item::Sym(s) => ret.push(*s),
+            Item::Rep(w, c) => (0..n + c).for_each(|_| ret.extend_from_slice(w)),
+        }
+    }
+    ret
+}
+
+/// `a` with words inserted so it becomes `b`, using as few words as possible
+fn insertions(a: &[u8], b: &[u8]) -> Option<Vec<Item>> {
+    const INF: u16 = u16::MAX;
+    let d = b.len().checked_sub(a.len()).filter(|&d| d > 0)?;
+    // `cost[x * (d + 1) + i][g]` == min words aligning `a[..x]` with `b[..x + i]`, `g == 1` if `b[x + i - 1]` is inserted
+    let idx = |x: usize, i: usize| x * (d + 1) + i;
+    let mut cost = vec![[INF; 2]; (a.len() + 1) * (d + 1)];
+    cost[0][0] = 0;
+    for x in 0..=a.len() {
+        for i in 0..=d {
+            for g in 0..2 {
+                let c = cost[idx(x, i)][g];
+                if c == INF {
+                    continue;
+                }
+                if x < a.len() && a[x] == b[x + i] {
+                    cost[idx(x + 1, i)][0] = cost[idx(x + 1, i)][0].min(c);
+                }
+                if i < d {
+                    cost[idx(x, i + 1)][1] = cost[idx(x, i + 1)][1].min(c + (g == 0) as u16);
+                }
+            }
+        }
+    }
+
+    let (mut x, mut i) = (a.len(), d);
+    let mut g = if cost[idx(x, i)][0] <= cost[idx(x, i)][1] { 0 } else { 1 };
+    if cost[idx(x, i)][g] == INF {
+        return None;
+    }
+    // (symbol, inserted) from the end
+    let mut ops = vec![];
+    while x + i > 0 {
+        let c = cost[idx(x, i)][g];
+        ops.push((b[x + i - 1], g == 1));
+        if g == 1 {
+            i -= 1;
+            g = if cost[idx(x, i)][1] == c { 1 } else { 0 };
+        } else {
+            x -= 1;
+            g = if cost[idx(x, i)][0] == c { 0 } else { 1 };
+        }
+    }
+    ops.reverse();
+
+    let mut items = vec![];
+    for (inserted, group) in &ops.into_iter().group_by(|&(_, inserted)| inserted) {
+        let symbols = group.map(|(s, _)| s);
+        if inserted {
+            items.push(Item::Rep(symbols.collect(), 0));
+        } else {
+            items.extend(symbols.map(Item::Sym));
+        }
+    }
+    Some(items)
+}
+
+/// `q> w` -> `w' q>` (or the same to the left) without reading anything outside of `w`
+fn shift(machine: &Machine, head: Head, word: &[u8], max_steps: usize) -> Option<Vec<u8>> {
+    let o = head.orient();
+    let mut sides = [vec![], vec![]];
+    sides[o] = word.to_vec();
+    let mut state = head.state;
+    let mut orient = o;
+    for _ in 0..max_steps {
+        let Some(symbol) = sides[orient].pop() else {
+            return (orient == o && state == head.state).then(|| std::mem::take(&mut sides[1 - o]));
+        };
+        let trans = machine.get_transition(symbol, state).filter(|t| t.head.state < machine.states())?;
+        sides[trans.head.op_orient()].push(trans.symbol);
+        state = trans.head.state;
+        orient = trans.head.orient();
+    }
+    None
+}
+
+struct Symbolic<'a> {
+    machine: &'a Machine,
+    config: &'a Config,
+    shifts: HashMap<(Head, Vec<u8>), Option<Vec<u8>>>,
+}
+
+impl<'a> Symbolic<'a> {
+    /// @returns `None` if the formula can't be advanced for every `n`
+    fn step(&mut self, f: &mut Formula) -> Option<()> {
+        let o = f.head.orient();
+        let symbol = match f.sides[o].pop() {
+            None => 0,
+            Some(Item::Sym(s)) => s,
+            Some(Item::Rep(w, c)) => {
+                let (machine, max_steps) = (self.machine, self.config.shift_steps);
+                let key = (f.head, w);
+                let shifted =
+                    self.shifts.entry(key.clone()).or_insert_with(|| shift(machine, key.0, &key.1, max_steps));
+                if let Some(shifted) = shifted {
+                    f.sides[1 - o].push(Item::Rep(shifted.clone(), c));
+                    return Some(());
+                }
+                if c == 0 {
+                    return None;
+                }
+                let w = key.1;
+                f.sides[o].push(Item::Rep(w.clone(), c - 1));
+                f.sides[o].extend(w[..w.len() - 1].iter().map(|&s| Item::Sym(s)));
+                w[w.len() - 1]
+            }
+        };
+        let trans =
+            self.machine.get_transition(symbol, f.head.state).filter(|t| t.head.state < self.machine.states())?;
+        f.sides[trans.head.op_orient()].push(Item::Sym(trans.symbol));
+        f.head = trans.head;
+        Some(())
+    }
+
+    /// `C(n)` reaches `C(n + 1)` for every `n`
+    fn prove(&mut self, formula: &Formula) -> bool {
+        let target = formula.next().canonical();
+        let mut f = formula.canonical();
+        for _ in 0..self.config.symbolic_steps {
+            if self.step(&mut f).is_none() {
+                return false;
+            }
+            if f.head == target.head && f.canonical() == target {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+struct Record {
+    state: u8,
+    steps: u64,
+    /// tape left of the head without the blanks at the far end
+    tape: Vec<u8>,
+}
+
+fn decide_right(machine: &Machine, config: &Config, mirrored: bool) -> ProverResult {
+    let mut sim = Simulator::new(machine);
+    let mut symbolic = Symbolic { machine, config, shifts: HashMap::new() };
+    let mut records: Vec<Record> = vec![];
+    let (mut max, mut attempts) = (0, 0);
+    loop {
+        if let Some(limit) = sim.check(config.limits) {
+            return limit;
+        }
+        if !sim.step() {
+            return sim.result();
+        }
+        if sim.position() <= max {
+            continue;
+        }
+        max = sim.position();
+        let left = &sim.tape()[..sim.head()];
+        let tape = left[left.iter().position(|&s| s != 0).unwrap_or(left.len())..].to_vec();
+        let k = Record { state: sim.state(), steps: sim.steps(), tape };
+
+        for j in records.iter().rev().filter(|j| j.state == k.state).take(8) {
+            let Some(d) = k.tape.len().checked_sub(j.tape.len()).filter(|&d| d > 0) else { continue };
+            let Some(i) = records.iter().rev().find(|i| i.state == k.state && i.tape.len() + d == j.tape.len()) else {
+                continue;
+            };
+            if attempts == config.attempts {
+                return ProverResult::Limit(format!("attempts: {}", config.attempts));
+            }
+            attempts += 1;
+            let Some(left) = insertions(&i.tape, &j.tape).filter(|left| expand(left, 2) == k.tape) else { continue };
+            let formula = Formula { sides: [left, vec![]], head: Head { state: k.state, orient: 1 } };
+            if symbolic.prove(&formula) {
+                return ProverResult::Infinite(format!(
+                    "bouncer: {} from step {}{}",
+                    formula.canonical(),
+                    i.steps,
+                    if mirrored { " mirrored" } else { "" }
+                ));
+            }
+        }
+        records.push(k);
+    }
+}
+
+pub fn decide(machine: &Machine, config: &Config) -> ProverResult {
+    match decide_right(machine, config, false) {
+        ProverResult::Limit(_) => decide_right(&machine.mirror(), config, true),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formula() {
+        for (machine, summary) in [
+            ("1RB1RA_1LA---", "bouncer: (11)^n 1 B> from step 1"),
+            ("1RB0LC_1LA0RB_0LB---", "bouncer: 1 1 1 0 (1)^n 0 B> from step 14"),
+            ("1RB---_1LC1LB_0LB1LA", "bouncer: 1 1 1 (10)^n B> from step 6 mirrored"),
+        ] {
+            let result = decide(&Machine::from(machine), &Config::default());
+            assert_eq!(result.to_string(), format!("Infinite(\"{}\")", summary), "{}", machine);
+        }
+    }
+
+    #[test]
+    fn halts() {
+        // BB(3) champion
+        let result = decide(&Machine::from("1RB1RZ_1LB0RC_1LC1LA"), &Config::default());
+        assert_eq!(result.to_string(), "Halt(21, 5)");
+    }
+}
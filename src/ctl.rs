@@ -0,0 +1,331 @@
+// closed tape language (finite automata reduction): a configuration `L (f, r) R` is accepted if the DFA reading
+// `L` from the far left ends in `q` and the NFA started in `v(q, f, r)` reads `R` (and then some blanks) into `a`;
+// the accepted set contains the start, is closed under steps & contains no halting configuration:
+//  - right move `(f, r) -> (w, R, t)`: `v(q, f, r) T_b ⊆ v(δ(q, w), t, b)` for every symbol `b`
+//  - left move `(f, r) -> (w, L, t)`: `v(δ(q, l), f, r) ⊆ v(q, t, l) T_w` for every `q` & symbol `l`
+//  - blanks: `δ(0, 0) == 0` on the left; `a ⊆ T_0 a` on the right
+//  - start: `v(0, A, 0) T_0^n` hits `a` for some `n`
+//  - halting `(f, r)`: nothing reachable from `v(q, f, r)` is in `a`
+// the NFA is built from the DFA as the least solution of the step constraints: every `v(q, f, r)` has its own
+// designated state, left moves add edges out of the designated states & `⊤` (all blanks) is the start's right side
+
+use crate::{ProverResult, machine::Machine, pipeline};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// max number of DFA states; `dfa_states * states * symbols` must be below 128
+    pub dfa_states: u8,
+    /// max number of searched (partial) DFAs
+    pub nodes: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { dfa_states: 4, nodes: 100_000 }
+    }
+}
+
+/// set of NFA states
+type Set = u128;
+
+fn image(set: Set, t: &[Set]) -> Set {
+    let mut ret = 0;
+    let mut set = set;
+    while set != 0 {
+        ret |= t[set.trailing_zeros() as usize];
+        set &= set - 1;
+    }
+    ret
+}
+
+/// everything reachable from `set` through any symbol
+fn reach(set: Set, t: &[Vec<Set>]) -> Set {
+    let mut ret = set;
+    loop {
+        let next = t.iter().fold(ret, |acc, t| acc | image(ret, t));
+        if next == ret {
+            return ret;
+        }
+        ret = next;
+    }
+}
+
+fn halts(machine: &Machine, state: u8, symbol: u8) -> bool {
+    machine.get_transition(symbol, state).filter(|t| t.head.state < machine.states()).is_none()
+}
+
+#[derive(Clone, Debug)]
+pub struct Certificate {
+    /// automata describe the mirrored machine
+    pub mirrored: bool,
+    /// `dfa[q][b]`
+    pub dfa: Vec<Vec<u8>>,
+    /// `v[(q * states + f) * symbols + r]`
+    pub v: Vec<Set>,
+    /// `t[b][x]` == successors of NFA state `x` reading `b`
+    pub t: Vec<Vec<Set>>,
+    pub a: Set,
+}
+
+impl std::fmt::Display for Certificate {
+    /// `ctl: dfa 0,1_1,0 (nfa 17)`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dfa = self
+            .dfa
+            .iter()
+            .map(|row| row.iter().map(|q| q.to_string()).collect::<Vec<_>>().join(","))
+            .collect::<Vec<_>>();
+        let nfa = self.t.first().map_or(0, |t| t.len());
+        write!(f, "ctl: dfa {} (nfa {}){}", dfa.join("_"), nfa, if self.mirrored { " mirrored" } else { "" })
+    }
+}
+
+/// independent check of all the conditions in the module comment
+pub fn verify(machine: &Machine, cert: &Certificate) -> bool {
+    let machine = if cert.mirrored { machine.mirror() } else { machine.clone() };
+    let (n, states, symbols) = (cert.dfa.len(), machine.states() as usize, machine.symbols() as usize);
+    let nfa = cert.t.first().map_or(0, |t| t.len());
+    let id = |q: usize, f: usize, r: usize| (q * states + f) * symbols + r;
+    let shapes_ok = n > 0
+        && nfa <= Set::BITS as usize
+        && cert.dfa.iter().all(|row| row.len() == symbols && row.iter().all(|&q| (q as usize) < n))
+        && cert.dfa[0][0] == 0
+        && cert.v.len() == n * states * symbols
+        && cert.t.len() == symbols
+        && cert.t.iter().all(|t| t.len() == nfa)
+        && cert.v.iter().chain(cert.t.iter().flatten()).chain([&cert.a]).all(|&set| nfa == 128 || set >> nfa == 0);
+    if !shapes_ok {
+        return false;
+    }
+
+    for q in 0..n {
+        for f in 0..states {
+            for r in 0..symbols {
+                let v = cert.v[id(q, f, r)];
+                let Some(trans) = machine.get_transition(r as u8, f as u8).filter(|t| (t.head.state as usize) < states)
+                else {
+                    // halting
+                    if reach(v, &cert.t) & cert.a != 0 {
+                        return false;
+                    }
+                    continue;
+                };
+                let (w, to) = (trans.symbol as usize, trans.head.state as usize);
+                if trans.head.orient == 1 {
+                    let q2 = cert.dfa[q][w] as usize;
+                    if (0..symbols).any(|b| image(v, &cert.t[b]) & !cert.v[id(q2, to, b)] != 0) {
+                        return false;
+                    }
+                } else {
+                    for (q1, l) in (0..n).flat_map(|q1| (0..symbols).map(move |l| (q1, l))) {
+                        if cert.dfa[q1][l] as usize == q && v & !image(cert.v[id(q1, to, l)], &cert.t[w]) != 0 {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // `a ⊆ T_0 a`
+    let mut a = cert.a;
+    while a != 0 {
+        let x = a.trailing_zeros() as usize;
+        if cert.t[0][x] & cert.a == 0 {
+            return false;
+        }
+        a &= a - 1;
+    }
+    // start
+    let mut start = cert.v[id(0, 0, 0)];
+    loop {
+        if start & cert.a != 0 {
+            return true;
+        }
+        let next = start | image(start, &cert.t[0]);
+        if next == start {
+            return false;
+        }
+        start = next;
+    }
+}
+
+struct Search<'a> {
+    machine: &'a Machine,
+    n: usize,
+    states: usize,
+    symbols: usize,
+    /// row-major `[q][b]`, `u8::MAX` == not chosen yet
+    dfa: Vec<u8>,
+    nodes: usize,
+    max_nodes: usize,
+}
+
+impl<'a> Search<'a> {
+    fn id(&self, q: usize, f: usize, r: usize) -> usize {
+        (q * self.states + f) * self.symbols + r
+    }
+
+    fn top(&self) -> usize {
+        self.n * self.states * self.symbols
+    }
+
+    fn delta(&self, q: usize, b: usize) -> Option<usize> {
+        Some(self.dfa[q * self.symbols + b]).filter(|&q| q != u8::MAX).map(|q| q as usize)
+    }
+
+    /// least solution of the step constraints using the chosen part of the DFA
+    fn closure(&self) -> (Vec<Set>, Vec<Vec<Set>>) {
+        let (n, states, symbols) = (self.n, self.states, self.symbols);
+        let mut v: Vec<Set> = (0..n * states * symbols).map(|x| 1 << x).collect();
+        v[self.id(0, 0, 0)] |= 1 << self.top();
+        let mut t = vec![vec![0; self.top() + 1]; symbols];
+        t[0][self.top()] = 1 << self.top();
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (q, f, r) in (0..n).flat_map(|q| (0..states).flat_map(move |f| (0..symbols).map(move |r| (q, f, r)))) {
+                let Some(trans) =
+                    self.machine.get_transition(r as u8, f as u8).filter(|t| (t.head.state as usize) < states)
+                else {
+                    continue;
+                };
+                let (w, to, from) = (trans.symbol as usize, trans.head.state as usize, v[self.id(q, f, r)]);
+                if trans.head.orient == 1 {
+                    if let Some(q2) = self.delta(q, w) {
+                        for (b, t) in t.iter().enumerate() {
+                            let (dst, img) = (self.id(q2, to, b), image(from, t));
+                            changed |= img & !v[dst] != 0;
+                            v[dst] |= img;
+                        }
+                    }
+                } else {
+                    for (q1, l) in (0..n).flat_map(|q1| (0..symbols).map(move |l| (q1, l))) {
+                        if self.delta(q1, l) == Some(q) {
+                            let edges = &mut t[w][self.id(q1, to, l)];
+                            changed |= from & !*edges != 0;
+                            *edges |= from;
+                        }
+                    }
+                }
+            }
+        }
+        (v, t)
+    }
+
+    /// NFA states from which a halting configuration is accepted by the NFA
+    fn halting(&self, v: &[Set], t: &[Vec<Set>]) -> Set {
+        let mut set = 0;
+        for (f, r) in (0..self.states).flat_map(|f| (0..self.symbols).map(move |r| (f, r))) {
+            if halts(self.machine, f as u8, r as u8) {
+                (0..self.n).for_each(|q| set |= v[self.id(q, f, r)]);
+            }
+        }
+        reach(set, t)
+    }
+
+    /// @returns `Err` with the `Limit` result when the node limit or the time budget is hit
+    fn search(&mut self, entry: usize) -> Result<Option<Certificate>, ProverResult> {
+        self.nodes += 1;
+        if self.nodes > self.max_nodes {
+            return Err(ProverResult::Limit(format!("nodes: {}", self.max_nodes)));
+        }
+        if pipeline::expired() {
+            return Err(ProverResult::Limit("time".to_owned()));
+        }
+        let (v, t) = self.closure();
+        let halting = self.halting(&v, &t);
+        if halting >> self.top() & 1 == 1 {
+            return Ok(None);
+        }
+
+        let used = self.dfa.iter().filter(|&&q| q != u8::MAX).max().map_or(0, |&q| q as usize) + 1;
+        if entry / self.symbols >= used {
+            // every used state has its row; `a` is the largest set avoiding `halting` with `a ⊆ T_0 a`
+            let mut a = !halting & ((1 << self.top()) | ((1 << self.top()) - 1));
+            loop {
+                let mut next = a;
+                let mut set = a;
+                while set != 0 {
+                    let x = set.trailing_zeros() as usize;
+                    if t[0][x] & a == 0 {
+                        next &= !(1 << x);
+                    }
+                    set &= set - 1;
+                }
+                if next == a {
+                    break;
+                }
+                a = next;
+            }
+            let dfa = self.dfa[..used * self.symbols].chunks(self.symbols).map(|row| row.to_vec()).collect();
+            let v = v[..self.id(used, 0, 0)].to_vec();
+            return Ok(Some(Certificate { mirrored: false, dfa, v, t, a }));
+        }
+
+        for q in 0..=used.min(self.n - 1) {
+            self.dfa[entry] = q as u8;
+            let ret = self.search(entry + 1);
+            self.dfa[entry] = u8::MAX;
+            if !matches!(ret, Ok(None)) {
+                return ret;
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// searches the machine first, then its mirror image
+/// @returns `Err(Limit("dfa states: n"))` if neither has a certificate with `n` dfa states, or the node limit result
+pub fn search(machine: &Machine, config: &Config) -> Result<Certificate, ProverResult> {
+    let (states, symbols) = (machine.states() as usize, machine.symbols() as usize);
+    let n = (config.dfa_states as usize).min((Set::BITS as usize - 1) / (states * symbols)).max(1);
+    let mut nodes = 0;
+    for (mirrored, machine) in [(false, machine.clone()), (true, machine.mirror())] {
+        let mut dfa = vec![u8::MAX; n * symbols];
+        dfa[0] = 0;
+        let mut search = Search { machine: &machine, n, states, symbols, dfa, nodes, max_nodes: config.nodes };
+        match search.search(1) {
+            Ok(Some(cert)) => return Ok(Certificate { mirrored, ..cert }),
+            Ok(None) => nodes = search.nodes,
+            Err(limit) => return Err(limit),
+        }
+    }
+    Err(ProverResult::Limit(format!("dfa states: {}", n)))
+}
+
+pub fn decide(machine: &Machine, config: &Config) -> ProverResult {
+    match search(machine, config) {
+        Ok(cert) => {
+            assert!(verify(machine, &cert), "invalid certificate {}", cert);
+            ProverResult::Infinite(cert.to_string())
+        }
+        Err(limit) => limit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn certificate() {
+        let machine = Machine::from("1RB1LA_0LA0RC_1LB---");
+        let cert = search(&machine, &Config::default()).unwrap();
+        assert!(verify(&machine, &cert));
+        assert_eq!(cert.to_string(), "ctl: dfa 0,1_2,3_3,3_3,3 (nfa 25)");
+        assert_eq!(decide(&machine, &Config::default()).to_string(), format!("Infinite(\"{}\")", cert));
+    }
+
+    #[test]
+    fn tampered_certificate() {
+        let machine = Machine::from("1RB0LB_0LA---");
+        let cert = search(&machine, &Config::default()).unwrap();
+        assert!(verify(&machine, &cert));
+        // no accepting nfa state, the start configuration is rejected
+        assert!(!verify(&machine, &Certificate { a: 0, ..cert.clone() }));
+        // BB(2) halts, so no certificate may verify for it
+        assert!(!verify(&Machine::from("1RB1LB_1LA1RZ"), &cert));
+    }
+}
@@ -0,0 +1,78 @@
+// cyclers (lin recursion): the machine is simulated until a configuration repeats exactly;
+// configuration == (state, symbol under the head, tape left & right of the head without the blank ends)
+
+use hashbrown::HashMap;
+
+use crate::{
+    ProverResult,
+    interner::{ITape, InternerTape, LEN_MAX},
+    machine::Machine,
+    simulator::{Limits, Simulator},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// the tapes beside the head are interned, so `space` is lowered to the longest internable tape
+    pub limits: Limits,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { limits: Limits { steps: 1000, space: Some(500) } }
+    }
+}
+
+pub fn decide(machine: &Machine, config: &Config) -> ProverResult {
+    let limits =
+        Limits { space: Some(config.limits.space.map_or(LEN_MAX, |space| space.min(LEN_MAX))), ..config.limits };
+    let mut sim = Simulator::new(machine);
+    let mut interner = InternerTape::new();
+    let mut seen: HashMap<(u8, u8, ITape, ITape), u64> = HashMap::new();
+    loop {
+        // interned tapes are at most `LEN_MAX` long
+        if let Some(limit) = sim.check(limits) {
+            return limit;
+        }
+        let (tape, head) = (sim.tape(), sim.head());
+        let start = tape[..head].iter().position(|&s| s != 0).unwrap_or(head);
+        let end = tape[head + 1..].iter().rposition(|&s| s != 0).map_or(head + 1, |end| head + end + 2);
+        let left = interner.get_or_insert(&tape[start..head]);
+        let right = interner.get_or_insert(&tape[head + 1..end]);
+        if let Some(prev) = seen.insert((sim.state(), sim.symbol(), left, right), sim.steps()) {
+            return ProverResult::Infinite(format!("cycler: period {}, preperiod {}", sim.steps() - prev, prev));
+        }
+        if !sim.step() {
+            return sim.result();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn period_and_preperiod() {
+        for (machine, summary) in [
+            ("1RB---_0LB0LB", "cycler: period 1, preperiod 3"),
+            ("1RB1RB_0LA---", "cycler: period 2, preperiod 1"),
+            ("1RB---_1LC0LB_1RC1RB", "cycler: period 8, preperiod 3"),
+        ] {
+            let result = decide(&Machine::from(machine), &Config::default());
+            assert_eq!(result.to_string(), format!("Infinite(\"{}\")", summary), "{}", machine);
+        }
+    }
+
+    #[test]
+    fn bouncer_never_repeats() {
+        let result = decide(&Machine::from("1RB1RA_1LA---"), &Config::default());
+        assert_eq!(result.to_string(), "Limit(\"steps: 1000\")");
+    }
+
+    #[test]
+    fn halts() {
+        // BB(4) champion
+        let result = decide(&Machine::from("1RB1LB_1LA0LC_1RZ1LD_1RD0RA"), &Config::default());
+        assert_eq!(result.to_string(), "Halt(107, 13)");
+    }
+}
@@ -0,0 +1,138 @@
+// halting segment: configurations are projected onto a segment of `len` cells; a node is (state, head position in
+// `-1..=len` where `-1` & `len` stand for anywhere left & right of the segment, cells which may be unknown)
+// all nodes from which a halting transition can be reached are searched backwards starting from the halting ones;
+// if for some position of the start in the segment no node is compatible with the start (state A, known cells 0),
+// the machine never halts
+
+use hashbrown::HashSet;
+
+use crate::{
+    ProverResult,
+    machine::{Machine, Transition},
+    pipeline,
+};
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// segment lengths to try, in order
+    pub lengths: Vec<usize>,
+    /// max number of nodes for one segment length
+    pub nodes: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { lengths: (1..=8).collect(), nodes: 100_000 }
+    }
+}
+
+const UNKNOWN: u8 = u8::MAX;
+
+/// (state, head position + 1, cells)
+type Node = (u8, usize, Vec<u8>);
+
+/// nodes before `trans` was executed in `state` reading `symbol`
+fn predecessors((_, pos, cells): &Node, state: u8, symbol: u8, trans: Transition) -> Vec<Node> {
+    let right = cells.len() + 1;
+    let prev = match (trans.head.orient, *pos) {
+        (1, 0) => vec![0],
+        (1, p) if p == right => vec![right, right - 1],
+        (1, p) => vec![p - 1],
+        (_, p) if p == right => vec![right],
+        (_, 0) => vec![0, 1],
+        (_, p) => vec![p + 1],
+    };
+    prev.into_iter()
+        .filter_map(|prev| {
+            let mut cells = cells.clone();
+            if prev != 0 && prev != right {
+                let cell = &mut cells[prev - 1];
+                if *cell != UNKNOWN && *cell != trans.symbol {
+                    return None;
+                }
+                *cell = symbol;
+            }
+            Some((state, prev, cells))
+        })
+        .collect()
+}
+
+fn decide_len(machine: &Machine, len: usize, max_nodes: usize) -> ProverResult {
+    let (states, symbols) = (machine.states(), machine.symbols());
+    let transitions = (0..states).flat_map(|state| (0..symbols).map(move |symbol| (state, symbol)));
+    // defined non-halting transitions by their target state
+    let mut into = vec![vec![]; states as usize];
+    let mut stack = vec![];
+    for (state, symbol) in transitions {
+        match machine.get_transition(symbol, state).filter(|t| t.head.state < states) {
+            Some(trans) => into[trans.head.state as usize].push((state, symbol, trans)),
+            None => {
+                for pos in 0..=len + 1 {
+                    let mut cells = vec![UNKNOWN; len];
+                    if pos != 0 && pos != len + 1 {
+                        cells[pos - 1] = symbol;
+                    }
+                    stack.push((state, pos, cells));
+                }
+            }
+        }
+    }
+
+    let mut nodes: HashSet<Node> = stack.iter().cloned().collect();
+    // start positions in the segment compatible with some node
+    let mut starts = vec![false; len];
+    while let Some(node) = stack.pop() {
+        let (state, pos, cells) = &node;
+        if *state == 0 && *pos != 0 && *pos != len + 1 && cells.iter().all(|&c| c == 0 || c == UNKNOWN) {
+            starts[pos - 1] = true;
+            if starts.iter().all(|&s| s) {
+                return ProverResult::Limit(format!("start reachable in segment {}", len));
+            }
+        }
+        for &(prev_state, symbol, trans) in &into[*state as usize] {
+            for prev in predecessors(&node, prev_state, symbol, trans) {
+                if !nodes.contains(&prev) {
+                    nodes.insert(prev.clone());
+                    stack.push(prev);
+                }
+            }
+        }
+        if nodes.len() > max_nodes {
+            return ProverResult::Limit(format!("nodes: {}", max_nodes));
+        }
+        if pipeline::expired() {
+            return ProverResult::Limit("time".to_owned());
+        }
+    }
+    let start = starts.iter().position(|&s| !s).unwrap();
+    ProverResult::Infinite(format!("halting segment: length {}, start {}, nodes {}", len, start, nodes.len()))
+}
+
+pub fn decide(machine: &Machine, config: &Config) -> ProverResult {
+    ProverResult::first_infinite(config.lengths.iter().copied(), "no segment length", |len| {
+        decide_len(machine, len, config.nodes)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_length() {
+        for (machine, summary) in [
+            ("1RB0RB_0LA---", "halting segment: length 2, start 0, nodes 19"),
+            ("1RB---_0RC0LC_1LA1LB", "halting segment: length 6, start 3, nodes 259"),
+        ] {
+            let result = decide(&Machine::from(machine), &Config::default());
+            assert_eq!(result.to_string(), format!("Infinite(\"{}\")", summary), "{}", machine);
+        }
+    }
+
+    #[test]
+    fn start_reachable() {
+        // BB(2) champion
+        let result = decide(&Machine::from("1RB1LB_1LA1RZ"), &Config::default());
+        assert_eq!(result.to_string(), "Limit(\"start reachable in segment 8\")");
+    }
+}
@@ -0,0 +1,144 @@
+// bbchallenge decider outputs: big-endian u32 indices into the seed database, see `seed_db`
+
+use anyhow::{Context, Result, ensure};
+use itertools::{EitherOrBoth, Itertools};
+use std::{convert::TryInto, io::Write, iter::FromIterator, path::Path};
+
+/// sorted & deduplicated machine indices
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IndexSet(Vec<u32>);
+
+impl IndexSet {
+    pub fn new(mut indices: Vec<u32>) -> IndexSet {
+        indices.sort_unstable();
+        indices.dedup();
+        IndexSet(indices)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<IndexSet> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).with_context(|| format!("can't read {}", path.display()))?;
+        ensure!(data.len() % 4 == 0, "{}: size {} is not a multiple of 4", path.display(), data.len());
+        Ok(IndexSet::new(data.chunks(4).map(|idx| u32::from_be_bytes(idx.try_into().unwrap())).collect()))
+    }
+
+    /// union of all files
+    pub fn load_all<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<IndexSet> {
+        paths.into_iter().try_fold(IndexSet::default(), |set, path| Ok(set.union(&IndexSet::load(path)?)))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = std::fs::File::create(path).with_context(|| format!("can't create {}", path.display()))?;
+        let mut w = std::io::BufWriter::new(file);
+        self.write(&mut w)?;
+        Ok(w.flush()?)
+    }
+
+    pub fn write(&self, mut w: impl Write) -> Result<()> {
+        self.0.iter().try_for_each(|idx| w.write_all(&idx.to_be_bytes()))?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, idx: u32) -> bool {
+        self.0.binary_search(&idx).is_ok()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn as_slice(&self) -> &[u32] {
+        &self.0
+    }
+
+    pub fn union(&self, other: &IndexSet) -> IndexSet {
+        IndexSet(self.iter().merge(other.iter()).dedup().collect())
+    }
+
+    pub fn intersection(&self, other: &IndexSet) -> IndexSet {
+        IndexSet(self.join(other).filter_map(|e| e.both().map(|(idx, _)| idx)).collect())
+    }
+
+    /// `self - other`
+    pub fn difference(&self, other: &IndexSet) -> IndexSet {
+        IndexSet(
+            self.join(other).filter_map(|e| if let EitherOrBoth::Left(idx) = e { Some(idx) } else { None }).collect(),
+        )
+    }
+
+    fn join<'a>(&'a self, other: &'a IndexSet) -> impl Iterator<Item = EitherOrBoth<u32, u32>> + 'a {
+        self.iter().merge_join_by(other.iter(), |a, b| a.cmp(b))
+    }
+}
+
+impl FromIterator<u32> for IndexSet {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        IndexSet::new(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for IndexSet {
+    type Item = u32;
+    type IntoIter = std::vec::IntoIter<u32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("bbc_index_set_{}_{}", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn load() {
+        let a = temp_file("a", &[0, 0, 1, 0, 0, 0, 0, 7, 0, 0, 0, 7, 1, 0, 0, 0]);
+        let b = temp_file("b", &[0, 0, 0, 2, 0, 0, 0, 7]);
+        let set = IndexSet::load(&a).unwrap();
+        assert_eq!(set.as_slice(), [7, 256, 1 << 24]);
+        let all = IndexSet::load_all([&a, &b]).unwrap();
+        assert_eq!(all.as_slice(), [2, 7, 256, 1 << 24]);
+
+        let mut bytes = vec![];
+        all.write(&mut bytes).unwrap();
+        all.save(&a).unwrap();
+        assert_eq!(std::fs::read(&a).unwrap(), bytes);
+        assert_eq!(IndexSet::load(&a).unwrap(), all);
+        [a, b].iter().for_each(|path| std::fs::remove_file(path).unwrap());
+    }
+
+    #[test]
+    fn odd_size_rejected() {
+        let path = temp_file("odd", &[0, 0, 0, 1, 0, 0]);
+        assert!(IndexSet::load(&path).err().unwrap().to_string().contains("size 6 is not a multiple of 4"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: IndexSet = [5, 1, 3, 3, 9].iter().copied().collect();
+        let b = IndexSet::new(vec![3, 4, 9, 10]);
+        assert_eq!(a.as_slice(), [1, 3, 5, 9]);
+        assert_eq!(a.union(&b).as_slice(), [1, 3, 4, 5, 9, 10]);
+        assert_eq!(a.intersection(&b).as_slice(), [3, 9]);
+        assert_eq!(a.difference(&b).as_slice(), [1, 5]);
+        assert_eq!(b.difference(&a).as_slice(), [4, 10]);
+        assert!(a.contains(5) && !a.contains(4));
+        assert!(a.intersection(&IndexSet::default()).is_empty());
+    }
+}
@@ -28,6 +28,8 @@ struct Opaque<T>(T);
 
 type Idx = u32;
 const IDX_MAX: usize = core::u32::MAX as usize;
+/// max length of an interned tape
+pub const LEN_MAX: usize = u16::MAX as usize;
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct ITape {
@@ -146,6 +148,7 @@ impl<T: Hash + PartialEq + Clone, S: BuildHasher> InternerTape<T, S> {
                     if itape_to_tape.len().checked_add(s.len()).map(|end| end > IDX_MAX).unwrap_or(true) {
                         panic!("InternerTape overflowed")
                     }
+                    assert!(s.len() <= LEN_MAX, "tape of {} symbols is too long to intern", s.len());
                     let base = itape_to_tape.len() as Idx;
                     let len = s.len() as u16;
                     itape_to_tape.extend_from_slice(s);
@@ -194,3 +197,25 @@ impl<'a, I: fmt::Display> fmt::Display for TapeFmt<'a, I> {
         it.try_for_each(|symbol| write!(f, "{}", symbol))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let mut interner = InternerTape::new();
+        let a = interner.get_or_insert(&[1, 0, 1]);
+        assert_eq!(interner.get_or_insert(&[1, 0, 1]), a);
+        assert_eq!(interner.get(&[1, 0, 1]), Some(a));
+        assert_eq!(interner.get(&[1, 0]), None);
+        assert_eq!(&interner[a], [1, 0, 1]);
+        assert_eq!(interner.get_or_insert(&vec![1; LEN_MAX]).len as usize, LEN_MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "too long to intern")]
+    fn too_long() {
+        InternerTape::new().get_or_insert(&vec![1u8; LEN_MAX + 1]);
+    }
+}
@@ -11,19 +11,33 @@ use std::panic::UnwindSafe;
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
-// pub mod ctl;
+pub mod backward_reasoning;
+pub mod bouncer;
+pub mod ctl;
+pub mod cycler;
+pub mod halting_segment;
+pub mod index_set;
 #[allow(unused)]
 mod interner;
 pub mod machine;
-// pub mod skelet_cps;
+pub mod mitm_wfar;
+pub mod ngram_cps;
+pub mod pipeline;
+pub mod regex;
+pub mod seed_db;
+pub mod simulator;
+pub mod skelet_cps;
+pub mod tnf;
+pub mod translated_cycler;
 mod ui;
 
 #[derive(Debug, Clone, parse_display::Display, parse_display::FromStr)]
 pub enum ProverResult {
-    #[display("{}")]
-    Halt,
-    #[display("{}")]
-    Infinite,
+    #[display("{}({steps}, {sigma})")]
+    Halt { steps: u64, sigma: u64 },
+    /// proof summary, e.g. `cycler: period 4, preperiod 10`
+    #[display("{}(\"{0}\")")]
+    Infinite(String),
     #[display("{}(\"{0}\")")]
     Limit(String),
     #[display("{}(\"{0}\")")]
@@ -31,6 +45,23 @@ pub enum ProverResult {
 }
 
 impl ProverResult {
+    /// tries the parameters in order, e.g. increasing segment lengths
+    /// @returns result of the first parameter proving the machine infinite, otherwise result of the last one
+    pub fn first_infinite<T>(
+        params: impl IntoIterator<Item = T>,
+        empty: &str,
+        mut decide: impl FnMut(T) -> ProverResult,
+    ) -> ProverResult {
+        let mut ret = ProverResult::Limit(empty.to_owned());
+        for param in params {
+            ret = decide(param);
+            if let ProverResult::Infinite(_) = ret {
+                break;
+            }
+        }
+        ret
+    }
+
     pub fn catch<F: FnOnce() -> R + UnwindSafe, R: Into<ProverResult>>(f: F) -> ProverResult {
         match std::panic::catch_unwind(|| f()) {
             Ok(ret) => ret.into(),
@@ -1,5 +1,5 @@
 use itertools::Itertools;
-use std::{convert::TryInto, fmt};
+use std::fmt;
 
 pub type Orientation = u8;
 
@@ -10,7 +10,7 @@ pub type Orientation = u8;
 //     Right = 1,
 // }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Head {
     pub state: u8,
     pub orient: u8,
@@ -42,7 +42,7 @@ impl fmt::Display for Head {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Transition {
     pub symbol: u8,
     pub head: Head,
@@ -68,6 +68,35 @@ impl Transition {
     pub fn last() -> Transition {
         Transition { symbol: 1, head: Head { state: 25, orient: 1 } } // 1RZ
     }
+
+    /// accepts `1RB`, marxen's `B1R` and undefined `---`; `Z` is the halting state unless the machine uses it
+    fn parse(trans: &[u8], states: u8, symbols: u8) -> Result<Transition, ParseErrorKind> {
+        let &[mut symbol, mut orient, mut state] = trans else {
+            return Err(ParseErrorKind::Transition(String::from_utf8_lossy(trans).into_owned()));
+        };
+        match symbol {
+            b'A'..=b'Z' => (state, symbol, orient) = (symbol, orient, state), // marxen's format B1R -> 1RB
+            b'-' if trans == b"---" => return Ok(Transition::undefined()),
+            _ => (),
+        }
+
+        let symbol = match symbol {
+            b'0'..=b'9' if symbol - b'0' < symbols => symbol - b'0',
+            _ => return Err(ParseErrorKind::Symbol(symbol as char)),
+        };
+        let orient = match orient {
+            b'L' => 0,
+            b'R' => 1,
+            _ => return Err(ParseErrorKind::Direction(orient as char)),
+        };
+        let state = match state {
+            b'Z' if states < 26 => 25,
+            b'A'..=b'Z' if state - b'A' < states => state - b'A',
+            b'A'..=b'Z' => return Err(ParseErrorKind::StateOutOfRange(state as char, states)),
+            _ => return Err(ParseErrorKind::State(state as char)),
+        };
+        Ok(Transition { symbol, head: Head { state, orient } })
+    }
 }
 
 impl fmt::Display for Transition {
@@ -92,63 +121,246 @@ impl fmt::Debug for Transition {
     }
 }
 
-// TODO: benchmark that version with dynamic symbol count & Option
-#[derive(Clone)]
+/// `Eq`/`Hash`/`Ord` compare the transition tables as written; use `canonical()` to ignore state names & mirroring
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Machine {
-    machine: Vec<[Transition; 2]>,
+    /// row-major `[state][symbol]`
+    machine: Vec<Transition>,
+    symbols: u8,
 }
 
 impl Machine {
+    /// all transitions undefined
+    pub fn new(states: u8, symbols: u8) -> Machine {
+        Machine { machine: vec![Transition::undefined(); states as usize * symbols as usize], symbols }
+    }
+
     #[inline]
     pub fn get_transition(&self, symbol: u8, state: u8) -> Option<Transition> {
-        let trans = self.machine[state as usize][symbol as usize];
+        let trans = self.machine[state as usize * self.symbols as usize + symbol as usize];
         (!trans.is_undefined()).then_some(trans)
     }
 
+    #[inline]
+    pub fn set_transition(&mut self, symbol: u8, state: u8, trans: Transition) {
+        self.machine[state as usize * self.symbols as usize + symbol as usize] = trans;
+    }
+
+    /// panics on malformed input, see `Machine::parse`
     pub fn from(machine: &str) -> Machine {
-        fn trans(trans: &[u8]) -> Transition {
-            let [mut symbol, mut orient, mut state]: [u8; 3] = trans.try_into().unwrap();
-            match symbol as char {
-                'A'..='Z' => (state, symbol, orient) = (symbol, orient, state), // marxen's format B1R -> 1RB
-                // '-' => (symbol, orient, state) = ('1' as u8, 'R' as u8, 'Z' as u8),
-                '-' => return Transition::undefined(),
-                _ => (),
-            }
-            Transition {
-                symbol: symbol - '0' as u8,
-                head: Head { orient: if orient == 'L' as u8 { 0 } else { 1 }, state: state - 'A' as u8 },
-            }
-        }
+        Machine::parse(machine).unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        let machine = machine.trim();
-        let it: Box<dyn Iterator<Item = &[u8]>> = if machine.contains("_") {
-            // new format
-            Box::new(machine.split("_").map(|row| row.as_bytes().chunks(3)).flatten())
-        } else if machine.contains(" ") {
-            // classic & ligocki double space
-            Box::new(machine.split_whitespace().map(str::as_bytes))
+    pub fn parse(machine: &str) -> Result<Machine, ParseError> {
+        let offset = |s: &str| s.as_ptr() as usize - machine.as_ptr() as usize;
+        let trimmed = machine.trim();
+        let format = if trimmed.contains('_') {
+            Format::Underscore
+        } else if trimmed.contains(' ') {
+            Format::Whitespace
         } else {
-            // compact bbchallenge.org
-            Box::new(machine.as_bytes().chunks(3))
+            Format::Compact
         };
+        let error = |kind, position| ParseError { format, position, kind };
+        if trimmed.is_empty() {
+            return Err(error(ParseErrorKind::Empty, None));
+        }
 
-        Machine { machine: it.map(trans).tuples().map(|(s0, s1)| [s0, s1]).collect() }
+        // (offset, transition) rows
+        let rows: Vec<Vec<(usize, &[u8])>> = match format {
+            Format::Underscore => trimmed
+                .split('_')
+                .map(|row| row.as_bytes().chunks(3).enumerate().map(|(i, t)| (offset(row) + 3 * i, t)).collect())
+                .collect(),
+            // ligocki double space separates rows
+            Format::Whitespace if trimmed.contains("  ") => trimmed
+                .split("  ")
+                .map(str::trim)
+                .filter(|row| !row.is_empty())
+                .map(|row| row.split_whitespace().map(|t| (offset(t), t.as_bytes())).collect())
+                .collect(),
+            // classic
+            Format::Whitespace => trimmed
+                .split_whitespace()
+                .map(|t| (offset(t), t.as_bytes()))
+                .chunks(2)
+                .into_iter()
+                .map(Iterator::collect)
+                .collect(),
+            Format::Compact => trimmed
+                .as_bytes()
+                .chunks(3)
+                .enumerate()
+                .map(|(i, t)| (offset(trimmed) + 3 * i, t))
+                .chunks(2)
+                .into_iter()
+                .map(Iterator::collect)
+                .collect(),
+        };
+
+        // only underscore rows can be empty, their offset is needed for diagnostics
+        let row_offsets: Vec<usize> = match format {
+            Format::Underscore => trimmed.split('_').map(offset).collect(),
+            _ => rows.iter().map(|row| row[0].0).collect(),
+        };
+        let states = rows.len();
+        // row width of the first row; classic & compact formats are always 2-symbol
+        let symbols = rows[0].len();
+        if !(1..=10).contains(&symbols) {
+            let offset = rows[0].get(10).map_or(offset(trimmed), |&(offset, _)| offset);
+            let position = Position { row: 0, transition: symbols.min(10), offset };
+            return Err(error(ParseErrorKind::Symbols(symbols), Some(position)));
+        }
+        // states are letters, `Z` can be the 26th one
+        if states > 26 {
+            let position = Position { row: 26, transition: 0, offset: row_offsets[26] };
+            return Err(error(ParseErrorKind::States(states), Some(position)));
+        }
+        // shapes of all rows first, so e.g. a truncated transition is not reported as a state out of range
+        for (row, transitions) in rows.iter().enumerate() {
+            if transitions.len() != symbols {
+                // first missing or superfluous transition
+                let offset = match transitions.get(symbols) {
+                    Some(&(offset, _)) => offset,
+                    None => transitions.last().map_or(row_offsets[row], |&(offset, t)| offset + t.len()),
+                };
+                let position = Position { row, transition: transitions.len().min(symbols), offset };
+                return Err(error(
+                    ParseErrorKind::RowLength { expected: symbols, found: transitions.len() },
+                    Some(position),
+                ));
+            }
+            if let Some((transition, &(offset, trans))) = transitions.iter().find_position(|(_, t)| t.len() != 3) {
+                let position = Position { row, transition, offset };
+                return Err(error(
+                    ParseErrorKind::Transition(String::from_utf8_lossy(trans).into_owned()),
+                    Some(position),
+                ));
+            }
+        }
+        // rows of these formats have no width of their own
+        let two_symbol = format == Format::Compact || (format == Format::Whitespace && !trimmed.contains("  "));
+        let mut machine = Vec::with_capacity(states * symbols);
+        for (row, transitions) in rows.iter().enumerate() {
+            for (transition, &(offset, trans)) in transitions.iter().enumerate() {
+                let position = Position { row, transition, offset };
+                machine.push(Transition::parse(trans, states as u8, symbols as u8).map_err(|kind| {
+                    let kind = match kind {
+                        ParseErrorKind::Symbol(symbol @ '2'..='9') if two_symbol => ParseErrorKind::TwoSymbol(symbol),
+                        kind => kind,
+                    };
+                    error(kind, Some(position))
+                })?);
+            }
+        }
+
+        Ok(Machine { machine, symbols: symbols as u8 })
     }
 
     #[inline(always)]
     pub fn states(&self) -> u8 {
-        self.machine.len() as u8
+        (self.machine.len() / self.symbols as usize) as u8
     }
 
     #[inline(always)]
     pub fn symbols(&self) -> u8 {
-        2
+        self.symbols
+    }
+
+    /// swaps left & right
+    pub fn mirror(&self) -> Machine {
+        let mut ret = self.clone();
+        ret.machine.iter_mut().filter(|t| !t.is_undefined()).for_each(|t| t.head.orient = 1 - t.head.orient);
+        ret
+    }
+
+    /// renames states in the order they are first referenced when reading the table from A row by row;
+    /// states unreachable from A keep their relative order after the reachable ones
+    pub fn rename_states(&self) -> Machine {
+        let states = self.states();
+        let mut order = vec![0];
+        let mut i = 0;
+        while i < order.len() {
+            for symbol in 0..self.symbols {
+                match self.get_transition(symbol, order[i]) {
+                    Some(t) if t.head.state < states && !order.contains(&t.head.state) => order.push(t.head.state),
+                    _ => (),
+                }
+            }
+            i += 1;
+        }
+        (0..states).for_each(|state| {
+            if !order.contains(&state) {
+                order.push(state)
+            }
+        });
+
+        let mut rename = vec![0; states as usize];
+        order.iter().enumerate().for_each(|(new, &old)| rename[old as usize] = new as u8);
+        let mut ret = Machine::new(states, self.symbols);
+        for (new, &old) in order.iter().enumerate() {
+            for symbol in 0..self.symbols {
+                if let Some(mut t) = self.get_transition(symbol, old) {
+                    if t.head.state < states {
+                        t.head.state = rename[t.head.state as usize];
+                    }
+                    ret.set_transition(symbol, new as u8, t);
+                }
+            }
+        }
+        ret
+    }
+
+    /// symbol `s` becomes `perm[s]`; `perm[0]` must be 0 as the blank symbol is fixed
+    pub fn permute_symbols(&self, perm: &[u8]) -> Machine {
+        assert!(perm.len() == self.symbols as usize && perm[0] == 0, "invalid permutation {:?}", perm);
+        let mut ret = Machine::new(self.states(), self.symbols);
+        for state in 0..self.states() {
+            for symbol in 0..self.symbols {
+                if let Some(mut t) = self.get_transition(symbol, state) {
+                    t.symbol = perm[t.symbol as usize];
+                    ret.set_transition(perm[symbol as usize], state, t);
+                }
+            }
+        }
+        ret
+    }
+
+    /// representative of all machines equal up to state names, mirroring & (for k > 2) non-blank symbol names:
+    /// halting transitions become undefined, the first move is to the right and out of the remaining candidates
+    /// the smallest one is taken
+    pub fn canonical(&self) -> Machine {
+        let (mut machine, states) = (self.clone(), self.states());
+        machine.machine.iter_mut().filter(|t| t.head.state >= states).for_each(|t| *t = Transition::undefined());
+        let mirrors = match machine.get_transition(0, 0) {
+            Some(t) if t.head.orient == 0 => vec![machine.mirror()],
+            Some(_) => vec![machine],
+            None => vec![machine.mirror(), machine],
+        };
+        mirrors
+            .iter()
+            .flat_map(|machine| {
+                (1..machine.symbols).permutations(machine.symbols as usize - 1).map(move |perm| {
+                    let perm: Vec<u8> = std::iter::once(0).chain(perm).collect();
+                    machine.permute_symbols(&perm).rename_states()
+                })
+            })
+            .min()
+            .unwrap()
+    }
+}
+
+impl std::str::FromStr for Machine {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Machine::parse(s)
     }
 }
 
 impl fmt::Display for Machine {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.machine.iter().enumerate().try_for_each(|(idx, state)| {
+        self.machine.chunks(self.symbols as usize).enumerate().try_for_each(|(idx, state)| {
             if idx != 0 {
                 write!(f, "_")?;
             }
@@ -156,3 +368,225 @@ impl fmt::Display for Machine {
         })
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, parse_display::Display)]
+#[display(style = "lowercase")]
+pub enum Format {
+    /// `1RB1LC_1RC1RB_...`
+    Underscore,
+    /// `1RB 1LC 1RC 1RB ...` or marxen's `B1R C1L ...`
+    Whitespace,
+    /// bbchallenge.org `1RB1LC1RC1RB...`
+    Compact,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    /// state
+    pub row: usize,
+    /// symbol
+    pub transition: usize,
+    /// byte offset into the parsed string
+    pub offset: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, parse_display::Display)]
+pub enum ParseErrorKind {
+    #[display("empty machine")]
+    Empty,
+    #[display("transition `{0}` is not 3 characters long")]
+    Transition(String),
+    #[display("invalid symbol `{0}`")]
+    Symbol(char),
+    #[display("invalid direction `{0}`")]
+    Direction(char),
+    #[display("invalid state `{0}`")]
+    State(char),
+    #[display("state `{0}` is out of range for {1}-state machine")]
+    StateOutOfRange(char, u8),
+    #[display("symbol `{0}` in a 2-symbol format, separate rows by `_` or two spaces for more symbols")]
+    TwoSymbol(char),
+    #[display("unsupported state count {0}")]
+    States(usize),
+    #[display("unsupported symbol count {0}")]
+    Symbols(usize),
+    #[display("row has {found} transitions, expected {expected}")]
+    RowLength { expected: usize, found: usize },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub format: Format,
+    pub position: Option<Position>,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} format", self.format)?;
+        if let Some(Position { row, transition, offset }) = self.position {
+            write!(f, ", row {}", row)?;
+            if row < 26 {
+                write!(f, " ({})", (row as u8 + b'A') as char)?;
+            }
+            write!(f, ", transition {} at byte {}", transition, offset)?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(machine: &str) -> (ParseErrorKind, Option<Position>) {
+        let err = Machine::parse(machine).err().unwrap();
+        (err.kind, err.position)
+    }
+
+    fn at(row: usize, transition: usize, offset: usize) -> Option<Position> {
+        Some(Position { row, transition, offset })
+    }
+
+    /// state `q` becomes `perm[q]`
+    fn permute_states(machine: &Machine, perm: &[u8]) -> Machine {
+        let (states, symbols) = (machine.states(), machine.symbols());
+        let mut ret = Machine::new(states, symbols);
+        for (state, symbol) in (0..states).flat_map(|state| (0..symbols).map(move |symbol| (state, symbol))) {
+            if let Some(mut t) = machine.get_transition(symbol, state) {
+                if t.head.state < states {
+                    t.head.state = perm[t.head.state as usize];
+                }
+                ret.set_transition(symbol, perm[state as usize], t);
+            }
+        }
+        ret
+    }
+
+    #[test]
+    fn canonical() {
+        for (machine, perm, canonical) in [
+            ("1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA", &[0, 1][..], "1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA"),
+            ("1RB2LA1RA1RA_1LB1LA3RB1RZ", &[0, 3, 1, 2][..], "1RB2LA1RA1RA_1LB1LA3RB---"),
+            ("1RB2LB1LC_1LA2RB1RB_1RZ2LA0RC", &[0, 2, 1][..], "1RB2LB1LC_1LA2RB1RB_---2LA0RC"),
+        ] {
+            let machine = Machine::from(machine);
+            // A stays the start state, the others are reversed
+            let states: Vec<u8> = std::iter::once(0).chain((1..machine.states()).rev()).collect();
+            let renamed = permute_states(&machine, &states);
+            let variants = [
+                machine.mirror(),
+                renamed.clone(),
+                renamed.rename_states(),
+                machine.permute_symbols(perm),
+                renamed.mirror().permute_symbols(perm),
+            ];
+            assert_eq!(machine.canonical().to_string(), canonical);
+            let canonicals: hashbrown::HashSet<Machine> = variants.iter().map(Machine::canonical).collect();
+            assert_eq!(canonicals.len(), 1, "{}", machine);
+            assert!(canonicals.contains(&machine.canonical()), "{}", machine);
+            assert!(variants[0] != machine && (machine.states() == 2 || variants[1] != machine), "{}", machine);
+        }
+    }
+
+    #[test]
+    fn formats() {
+        for machine in ["1RB1LB_1LA1RZ", "1RB 1LB 1LA 1RZ", "1RB 1LB  1LA 1RZ", "B1R B1L A1L Z1R", "1RB1LB1LA1RZ"] {
+            assert_eq!(Machine::parse(machine).unwrap().to_string(), "1RB1LB_1LA1RZ", "{}", machine);
+        }
+    }
+
+    #[test]
+    fn symbol_count() {
+        // BB(2, 4) champion
+        let champion = "1RB2LA1RA1RA_1LB1LA3RB1RZ";
+        for machine in [champion, "1RB 2LA 1RA 1RA  1LB 1LA 3RB 1RZ", "B1R A2L A1R A1R  B1L A1L B3R Z1R"] {
+            let machine = Machine::parse(machine).unwrap();
+            assert_eq!((machine.states(), machine.symbols()), (2, 4));
+            assert_eq!(machine.to_string(), champion);
+        }
+        let result = crate::simulator::simulate(&Machine::from(champion), crate::simulator::Limits::steps(10_000_000));
+        assert_eq!(result.to_string(), "Halt(3932964, 2050)");
+
+        let machine = "1RB2LB1LC_1LA2RB1RB_1RZ2LA0RC";
+        assert_eq!(Machine::from(machine).to_string(), machine);
+        assert_eq!(Machine::from(machine).symbols(), 3);
+    }
+
+    #[test]
+    fn two_symbol_formats() {
+        assert_eq!(parse_error("1RB 2LA 1RA 1RA 1LB 1LA 3RB 1RZ"), (ParseErrorKind::TwoSymbol('2'), at(0, 1, 4)));
+        assert_eq!(parse_error("1RB2LA1RA1RA1LB1LA3RB1RZ"), (ParseErrorKind::TwoSymbol('2'), at(0, 1, 3)));
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(parse_error("  "), (ParseErrorKind::Empty, None));
+    }
+
+    #[test]
+    fn transition() {
+        assert_eq!(parse_error("1RB1LB_1LA1R"), (ParseErrorKind::Transition("1R".to_owned()), at(1, 1, 10)));
+        // truncated before the transitions are read, `B` would be out of range
+        assert_eq!(parse_error("1RB1L"), (ParseErrorKind::Transition("1L".to_owned()), at(0, 1, 3)));
+    }
+
+    #[test]
+    fn symbol() {
+        assert_eq!(parse_error("1RB2LB_1LA1RZ"), (ParseErrorKind::Symbol('2'), at(0, 1, 3)));
+    }
+
+    #[test]
+    fn direction() {
+        assert_eq!(parse_error("1RB1LB_1XA1RZ"), (ParseErrorKind::Direction('X'), at(1, 0, 7)));
+    }
+
+    #[test]
+    fn state() {
+        assert_eq!(parse_error("1RB1LB_1L91RZ"), (ParseErrorKind::State('9'), at(1, 0, 7)));
+        assert_eq!(parse_error("1RB1LC_1LA1RZ"), (ParseErrorKind::StateOutOfRange('C', 2), at(0, 1, 3)));
+    }
+
+    #[test]
+    fn symbols() {
+        let machine = format!("{}_1RB", "1RB".repeat(11));
+        assert_eq!(parse_error(&machine), (ParseErrorKind::Symbols(11), at(0, 10, 30)));
+        assert_eq!(parse_error("_1RB"), (ParseErrorKind::Symbols(0), at(0, 0, 0)));
+    }
+
+    #[test]
+    fn states() {
+        let machine = vec!["1RB1LB"; 27].join("_");
+        assert_eq!(parse_error(&machine), (ParseErrorKind::States(27), at(26, 0, 182)));
+        let machine = vec!["1RB1LB"; 257].join("_");
+        assert_eq!(parse_error(&machine), (ParseErrorKind::States(257), at(26, 0, 182)));
+        assert_eq!(Machine::parse(&vec!["1RZ1LB"; 26].join("_")).unwrap().states(), 26);
+    }
+
+    #[test]
+    fn display() {
+        let kind = ParseErrorKind::RowLength { expected: 2, found: 1 };
+        let err = ParseError { format: Format::Underscore, position: at(1, 1, 10), kind: kind.clone() };
+        assert_eq!(
+            err.to_string(),
+            "underscore format, row 1 (B), transition 1 at byte 10: row has 1 transitions, expected 2"
+        );
+        let err = ParseError { format: Format::Underscore, position: at(300, 1, 10), kind };
+        assert_eq!(
+            err.to_string(),
+            "underscore format, row 300, transition 1 at byte 10: row has 1 transitions, expected 2"
+        );
+    }
+
+    #[test]
+    fn row_length() {
+        let missing = ParseErrorKind::RowLength { expected: 2, found: 1 };
+        assert_eq!(parse_error("1RB1LB_1LA"), (missing, at(1, 1, 10)));
+        let superfluous = ParseErrorKind::RowLength { expected: 2, found: 3 };
+        assert_eq!(parse_error("1RB1LB_1LA1RZ1RZ"), (superfluous, at(1, 2, 13)));
+        let empty = ParseErrorKind::RowLength { expected: 2, found: 0 };
+        assert_eq!(parse_error("1RB1LB__1LA1RZ"), (empty, at(1, 0, 7)));
+    }
+}
@@ -0,0 +1,390 @@
+// meet-in-the-middle weighted finite automata reduction: a configuration `L (f, r) R` is abstracted into the tuple
+// `(p, f, r, q)` where the left DFA reading `L` from the far left ends in `p` & the right DFA reading `R` from the far
+// right ends in `q`; every transition of both automata has a non-negative weight & the weight of the configuration
+// is the weight of `L` minus the weight of `R`
+// a configuration is accepted if its tuple has a threshold & its weight is at least the threshold; the accepted set
+// is closed under steps if the threshold of every successor tuple is at most the threshold plus the weight change:
+//  - right move `(f, r) -> (w, R, t)`: `(δL(p, w), t, b, q')` for every `q'` & `b` with `δR(q', b) == q`,
+//    the weight grows by `wL(p, w) + wR(q', b)`
+//  - left move `(f, r) -> (w, L, t)`: `(p', t, b, δR(q, w))` for every `p'` & `b` with `δL(p', b) == p`,
+//    the weight drops by `wL(p', b) + wR(q, w)`
+//  - start: `(0, A, 0, 0)` has weight 0 (blanks at the far ends loop in state 0 with weight 0)
+//  - halting `(f, r)`: the threshold is above the max weight of a left word ending in `p` minus the min weight of a
+//    right word ending in `q`
+// DFAs of both sides are met in pairs & the weighted variants of every pair are generated lazily, as there are
+// exponentially many; the thresholds of a pair are the least ones (shortest paths from the start)
+
+use itertools::Itertools;
+use std::{collections::VecDeque, fmt};
+
+use crate::{ProverResult, machine::Machine, pipeline};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// max number of DFA states on each side
+    pub dfa_states: usize,
+    /// max weight of a transition
+    pub max_weight: u32,
+    /// max number of tried (left, right) pairs
+    pub pairs: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { dfa_states: 2, max_weight: 1, pairs: 10_000 }
+    }
+}
+
+/// DFA with weighted transitions, state 0 is the start
+#[derive(Clone, Debug)]
+pub struct Automaton {
+    /// `dfa[p][b]`
+    pub dfa: Vec<Vec<u8>>,
+    /// `weights[p][b]`
+    pub weights: Vec<Vec<u32>>,
+}
+
+impl Automaton {
+    fn edges(&self) -> impl Iterator<Item = (usize, usize, i64)> + '_ {
+        self.dfa
+            .iter()
+            .zip(&self.weights)
+            .enumerate()
+            .flat_map(|(p, (row, weights))| row.iter().zip(weights).map(move |(&q, &w)| (p, q as usize, w as i64)))
+    }
+
+    /// min weight of a word leading to every state, `None` == unreachable
+    fn min_weights(&self) -> Vec<Option<i64>> {
+        let mut ret = vec![None; self.dfa.len()];
+        ret[0] = Some(0);
+        for _ in 0..self.dfa.len() {
+            for (p, q, w) in self.edges() {
+                if let Some(d) = ret[p] {
+                    if !matches!(ret[q], Some(e) if e <= d + w) {
+                        ret[q] = Some(d + w);
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// max weight of a word leading to every state, `None` == unreachable or unbounded (behind a positive cycle)
+    fn max_weights(&self) -> Vec<Option<i64>> {
+        let n = self.dfa.len();
+        let mut ret = vec![None; n];
+        ret[0] = Some(0);
+        for _ in 0..n {
+            for (p, q, w) in self.edges() {
+                if let Some(d) = ret[p] {
+                    if !matches!(ret[q], Some(e) if e >= d + w) {
+                        ret[q] = Some(d + w);
+                    }
+                }
+            }
+        }
+        // states still growing & everything after them are unbounded
+        let mut unbounded = vec![false; n];
+        for _ in 0..n {
+            for (p, q, w) in self.edges() {
+                if let Some(d) = ret[p] {
+                    if unbounded[p] || !matches!(ret[q], Some(e) if e >= d + w) {
+                        unbounded[q] = true;
+                    }
+                }
+            }
+        }
+        ret.iter().zip(unbounded).map(|(&max, unbounded)| max.filter(|_| !unbounded)).collect()
+    }
+}
+
+impl fmt::Display for Automaton {
+    /// `0,1_1,0 (0,1_0,0)`: transitions & their weights row by row
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn table<T: ToString>(rows: &[Vec<T>]) -> String {
+            rows.iter().map(|row| row.iter().map(|x| x.to_string()).join(",")).join("_")
+        }
+        write!(f, "{} ({})", table(&self.dfa), table(&self.weights))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Certificate {
+    /// automata describe the mirrored machine
+    pub mirrored: bool,
+    /// left & right automaton
+    pub sides: [Automaton; 2],
+    /// `thresholds[((p * states + f) * symbols + r) * right states + q]`; `None` == no configuration accepted,
+    /// `i64::MIN` == every weight accepted
+    pub thresholds: Vec<Option<i64>>,
+}
+
+impl fmt::Display for Certificate {
+    /// `mitm wfar: left 0,1_1,0 (0,0_1,0), right 0,1_0,1 (0,1_0,0)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [left, right] = &self.sides;
+        write!(f, "mitm wfar: left {}, right {}{}", left, right, if self.mirrored { " mirrored" } else { "" })
+    }
+}
+
+/// tuples `(p, f, r, q)` of a (left, right) pair of automata
+struct Tuples<'a> {
+    machine: &'a Machine,
+    sides: [&'a Automaton; 2],
+    states: usize,
+    symbols: usize,
+}
+
+impl<'a> Tuples<'a> {
+    fn len(&self) -> usize {
+        self.sides[0].dfa.len() * self.states * self.symbols * self.sides[1].dfa.len()
+    }
+
+    fn id(&self, p: usize, f: usize, r: usize, q: usize) -> usize {
+        ((p * self.states + f) * self.symbols + r) * self.sides[1].dfa.len() + q
+    }
+
+    fn tuple(&self, id: usize) -> (usize, usize, usize, usize) {
+        let (rest, q) = (id / self.sides[1].dfa.len(), id % self.sides[1].dfa.len());
+        let (rest, r) = (rest / self.symbols, rest % self.symbols);
+        (rest / self.states, rest % self.states, r, q)
+    }
+
+    /// successor tuples with the weight changes, `None` if the tuple halts
+    fn successors(&self, id: usize) -> Option<Vec<(usize, i64)>> {
+        let (p, f, r, q) = self.tuple(id);
+        let trans = self.machine.get_transition(r as u8, f as u8).filter(|t| (t.head.state as usize) < self.states)?;
+        let (w, t) = (trans.symbol as usize, trans.head.state as usize);
+        let [left, right] = self.sides;
+        let mut ret = vec![];
+        if trans.head.orient == 1 {
+            let (p2, dw) = (left.dfa[p][w] as usize, left.weights[p][w] as i64);
+            for q2 in 0..right.dfa.len() {
+                for b in (0..self.symbols).filter(|&b| right.dfa[q2][b] as usize == q) {
+                    ret.push((self.id(p2, t, b, q2), dw + right.weights[q2][b] as i64));
+                }
+            }
+        } else {
+            let (q2, dw) = (right.dfa[q][w] as usize, right.weights[q][w] as i64);
+            for p2 in 0..left.dfa.len() {
+                for b in (0..self.symbols).filter(|&b| left.dfa[p2][b] as usize == p) {
+                    ret.push((self.id(p2, t, b, q2), -(dw + left.weights[p2][b] as i64)));
+                }
+            }
+        }
+        Some(ret)
+    }
+
+    /// a halting tuple accepts nothing with the threshold
+    fn excluded(&self, id: usize, threshold: i64, max_left: &[Option<i64>], min_right: &[Option<i64>]) -> bool {
+        let (p, _, _, q) = self.tuple(id);
+        match (max_left[p], min_right[q]) {
+            (Some(max), Some(min)) => threshold != i64::MIN && threshold > max - min,
+            _ => false,
+        }
+    }
+
+    /// least thresholds: shortest paths from the start, `i64::MIN` on or behind negative cycles
+    fn thresholds(&self) -> Vec<Option<i64>> {
+        let start = self.id(0, 0, 0, 0);
+        let mut ret = vec![None; self.len()];
+        ret[start] = Some(0);
+        let (mut updates, mut queued) = (vec![0; self.len()], vec![false; self.len()]);
+        let mut queue = VecDeque::from(vec![start]);
+        queued[start] = true;
+        while let Some(id) = queue.pop_front() {
+            queued[id] = false;
+            let t = ret[id].unwrap();
+            for (id2, d) in self.successors(id).unwrap_or_default() {
+                let mut t2 = if t == i64::MIN { t } else { t + d };
+                if matches!(ret[id2], Some(e) if e <= t2) {
+                    continue;
+                }
+                // without negative cycles a threshold decreases at most `len` times
+                updates[id2] += 1;
+                if updates[id2] > self.len() {
+                    t2 = i64::MIN;
+                }
+                ret[id2] = Some(t2);
+                if !queued[id2] {
+                    queued[id2] = true;
+                    queue.push_back(id2);
+                }
+            }
+        }
+        ret
+    }
+
+    /// halting tuples with a threshold
+    fn halting(&self, thresholds: &[Option<i64>]) -> Vec<usize> {
+        (0..self.len()).filter(|&id| thresholds[id].is_some() && self.successors(id).is_none()).collect()
+    }
+}
+
+/// independent check of all the conditions in the module comment
+pub fn verify(machine: &Machine, cert: &Certificate) -> bool {
+    let machine = if cert.mirrored { machine.mirror() } else { machine.clone() };
+    let (states, symbols) = (machine.states() as usize, machine.symbols() as usize);
+    let shapes_ok = cert.sides.iter().all(|a| {
+        let n = a.dfa.len();
+        n > 0
+            && a.weights.len() == n
+            && a.dfa.iter().zip(&a.weights).all(|(row, weights)| {
+                row.len() == symbols && weights.len() == symbols && row.iter().all(|&q| (q as usize) < n)
+            })
+            && a.dfa[0][0] == 0
+            && a.weights[0][0] == 0
+            && a.min_weights().iter().all(|w| w.is_some())
+    });
+    let [left, right] = &cert.sides;
+    let tuples = Tuples { machine: &machine, sides: [left, right], states, symbols };
+    if !shapes_ok || cert.thresholds.len() != tuples.len() {
+        return false;
+    }
+
+    let (max_left, min_right) = (left.max_weights(), right.min_weights());
+    let start_ok = matches!(cert.thresholds[tuples.id(0, 0, 0, 0)], Some(t) if t <= 0);
+    start_ok
+        && cert.thresholds.iter().enumerate().all(|(id, &threshold)| {
+            let Some(t) = threshold else { return true };
+            match tuples.successors(id) {
+                None => tuples.excluded(id, t, &max_left, &min_right),
+                Some(succ) => succ.into_iter().all(|(id2, d)| match cert.thresholds[id2] {
+                    Some(i64::MIN) => true,
+                    Some(t2) => t != i64::MIN && t2 <= t + d,
+                    None => false,
+                }),
+            }
+        })
+}
+
+/// every automaton with exactly `n` states, all reachable in the order of first use; stops early when the time budget
+/// is spent
+fn dfas(n: usize, symbols: usize) -> Vec<Vec<Vec<u8>>> {
+    fn fill(dfa: &mut Vec<u8>, n: usize, symbols: usize, used: usize, ret: &mut Vec<Vec<Vec<u8>>>) {
+        if dfa.len() == n * symbols {
+            if used == n {
+                ret.push(dfa.chunks(symbols).map(|row| row.to_vec()).collect());
+            }
+            return;
+        }
+        if dfa.len() / symbols >= used || pipeline::expired() {
+            return;
+        }
+        for q in 0..=used.min(n - 1) {
+            dfa.push(q as u8);
+            fill(dfa, n, symbols, used.max(q + 1), ret);
+            dfa.pop();
+        }
+    }
+    let mut ret = vec![];
+    fill(&mut vec![0], n, symbols, 1, &mut ret);
+    ret
+}
+
+/// weighted variants of `dfa` generated lazily, all weights 0 first
+fn variants<'a>(dfa: &'a [Vec<u8>], config: &Config) -> impl Iterator<Item = Automaton> + 'a {
+    let symbols = dfa[0].len();
+    let weights = (1..dfa.len() * symbols).map(|_| 0..=config.max_weight).multi_cartesian_product();
+    weights.map(move |weights| {
+        let weights: Vec<u32> = std::iter::once(0).chain(weights).collect();
+        Automaton { dfa: dfa.to_vec(), weights: weights.chunks(symbols).map(|row| row.to_vec()).collect() }
+    })
+}
+
+/// pairs every weighted left automaton with every right one, both orientations of the machine
+/// @returns `Err` once all pairs up to `dfa_states` & `max_weight` failed or more than `pairs` were tried
+pub fn search(machine: &Machine, config: &Config) -> Result<Certificate, ProverResult> {
+    let (states, symbols) = (machine.states() as usize, machine.symbols() as usize);
+    let dfas: Vec<Vec<Vec<u8>>> = (1..=config.dfa_states).flat_map(|n| dfas(n, symbols)).collect();
+    let mut pairs = 0;
+    // every tried pair, including the unweighted ones, counts against the limits
+    let mut next_pair = || {
+        pairs += 1;
+        if pairs > config.pairs {
+            return Err(ProverResult::Limit(format!("pairs: {}", config.pairs)));
+        }
+        if pipeline::expired() {
+            return Err(ProverResult::Limit("time".to_owned()));
+        }
+        Ok(())
+    };
+    for (mirrored, machine) in [(false, machine.clone()), (true, machine.mirror())] {
+        for left_dfa in &dfas {
+            for right_dfa in &dfas {
+                next_pair()?;
+                // without weights every reachable tuple has threshold 0, which excludes no halting tuple
+                let (left, right) =
+                    (variants(left_dfa, config).next().unwrap(), variants(right_dfa, config).next().unwrap());
+                let tuples = Tuples { machine: &machine, sides: [&left, &right], states, symbols };
+                let thresholds = tuples.thresholds();
+                let halting = tuples.halting(&thresholds);
+                if halting.is_empty() {
+                    return Ok(Certificate { mirrored, sides: [left, right], thresholds });
+                }
+                let halting: Vec<usize> = halting.iter().map(|&id| tuples.tuple(id).0).collect();
+                for left in variants(left_dfa, config) {
+                    // the left side has to bound the weights of words ending in halting tuples
+                    let max_left = left.max_weights();
+                    if !halting.iter().all(|&p| max_left[p].is_some()) {
+                        continue;
+                    }
+                    for right in variants(right_dfa, config) {
+                        next_pair()?;
+                        let min_right = right.min_weights();
+                        let tuples = Tuples { machine: &machine, sides: [&left, &right], states, symbols };
+                        let thresholds = tuples.thresholds();
+                        let halting = tuples.halting(&thresholds);
+                        if halting.iter().all(|&id| tuples.excluded(id, thresholds[id].unwrap(), &max_left, &min_right))
+                        {
+                            return Ok(Certificate { mirrored, sides: [left, right], thresholds });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if pipeline::expired() {
+        return Err(ProverResult::Limit("time".to_owned()));
+    }
+    Err(ProverResult::Limit(format!("dfa states: {}, max weight: {}", config.dfa_states, config.max_weight)))
+}
+
+pub fn decide(machine: &Machine, config: &Config) -> ProverResult {
+    match search(machine, config) {
+        Ok(cert) => {
+            assert!(verify(machine, &cert), "invalid certificate {}", cert);
+            ProverResult::Infinite(cert.to_string())
+        }
+        Err(limit) => limit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn certificate() {
+        for (machine, summary) in [
+            ("1RB1RA_1LA---", "mitm wfar: left 0,1_0,0 (0,0_0,0), right 0,1_0,0 (0,1_1,0)"),
+            ("1RB0LB_1RC0LC_0LA---", "mitm wfar: left 0,1_1,1 (0,0_0,0), right 0,1_0,1 (0,0_1,1) mirrored"),
+        ] {
+            let result = decide(&Machine::from(machine), &Config::default());
+            assert_eq!(result.to_string(), format!("Infinite(\"{}\")", summary), "{}", machine);
+        }
+    }
+
+    #[test]
+    fn tampered_certificate() {
+        let machine = Machine::from("1RB1RA_1LA---");
+        let cert = search(&machine, &Config::default()).unwrap();
+        assert!(verify(&machine, &cert));
+        // the start configuration is not accepted
+        let mut thresholds = cert.thresholds.clone();
+        thresholds[0] = None;
+        assert!(!verify(&machine, &Certificate { thresholds, ..cert.clone() }));
+        // BB(2) halts, so no certificate may verify for it
+        assert!(!verify(&Machine::from("1RB1LB_1LA1RZ"), &cert));
+    }
+}
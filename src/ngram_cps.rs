@@ -0,0 +1,151 @@
+// n-gram closed position set: a configuration is abstracted into a local one, the head with `radius` cells on each
+// side, while the rest of the tape is abstracted into the sets of n-grams (words of `radius` cells) seen on the left
+// & on the right of the head; a right step pushes a new n-gram into the left set & extends the right context by any
+// n-gram of the right set overlapping it (the same to the left)
+// with history, every cell also remembers the last states in which it was written, which splits n-grams otherwise
+// merged; if the local configurations & both sets close without reaching a halting transition, the machine never halts
+
+use hashbrown::HashSet;
+
+use crate::{ProverResult, machine::Machine, pipeline};
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// radii (n-gram lengths) to try, in order
+    pub radii: Vec<usize>,
+    /// number of last writing states remembered by every cell
+    pub history: u32,
+    /// max number of local configurations for one radius
+    pub configurations: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { radii: (1..=4).collect(), history: 0, configurations: 100_000 }
+    }
+}
+
+/// (symbol, history); the history holds the last writing states as digits in base `states + 1`, `0` == none
+type Cell = (u8, u32);
+
+/// (state, left context in tape order, cell under the head, right context in tape order)
+type Local = (u8, Vec<Cell>, Cell, Vec<Cell>);
+
+struct Ngrams<'a> {
+    machine: &'a Machine,
+    /// `(states + 1)^history`
+    modulus: u32,
+    locals: HashSet<Local>,
+    queue: Vec<Local>,
+    /// `sides[orient]`: n-grams left (0) & right (1) of the head
+    sides: [HashSet<Vec<Cell>>; 2],
+}
+
+impl<'a> Ngrams<'a> {
+    fn push(&mut self, local: Local) {
+        if self.locals.insert(local.clone()) {
+            self.queue.push(local);
+        }
+    }
+
+    /// a new n-gram on `side` may extend the context of local configurations already stepped, they are stepped again
+    fn add_ngram(&mut self, side: usize, ngram: Vec<Cell>) {
+        let extended = |(_, left, _, right): &&Local| match side {
+            0 => ngram[1..] == left[..left.len() - 1],
+            _ => ngram[..ngram.len() - 1] == right[1..],
+        };
+        if !self.sides[side].contains(&ngram) {
+            self.queue.extend(self.locals.iter().filter(extended).cloned());
+            self.sides[side].insert(ngram);
+        }
+    }
+
+    /// @returns `Some` if the run can't be closed
+    fn step(&mut self, (state, left, (symbol, history), right): &Local) -> Option<ProverResult> {
+        let trans = match self.machine.get_transition(*symbol, *state) {
+            Some(trans) if trans.head.state < self.machine.states() => trans,
+            _ => {
+                return Some(ProverResult::Limit(format!("halting {}{} reachable", (state + b'A') as char, symbol)));
+            }
+        };
+        let written = (trans.symbol, (history * (self.machine.states() as u32 + 1) + *state as u32 + 1) % self.modulus);
+        if trans.head.orient == 1 {
+            let mut pushed = left[1..].to_vec();
+            pushed.push(written);
+            self.add_ngram(0, pushed.clone());
+            let next: Vec<Vec<Cell>> =
+                self.sides[1].iter().filter(|g| g[..g.len() - 1] == right[1..]).cloned().collect();
+            for next in next {
+                self.push((trans.head.state, pushed.clone(), right[0], next));
+            }
+        } else {
+            let mut pushed = vec![written];
+            pushed.extend_from_slice(&right[..right.len() - 1]);
+            self.add_ngram(1, pushed.clone());
+            let next: Vec<Vec<Cell>> =
+                self.sides[0].iter().filter(|g| g[1..] == left[..left.len() - 1]).cloned().collect();
+            for next in next {
+                self.push((trans.head.state, next, left[left.len() - 1], pushed.clone()));
+            }
+        }
+        None
+    }
+}
+
+fn decide_radius(machine: &Machine, radius: usize, config: &Config) -> ProverResult {
+    let blank = vec![(0, 0); radius];
+    let mut ngrams = Ngrams {
+        machine,
+        modulus: (machine.states() as u32 + 1).pow(config.history),
+        locals: HashSet::new(),
+        queue: vec![],
+        sides: [std::iter::once(blank.clone()).collect(), std::iter::once(blank.clone()).collect()],
+    };
+    ngrams.push((0, blank.clone(), (0, 0), blank));
+    while let Some(local) = ngrams.queue.pop() {
+        if ngrams.locals.len() > config.configurations {
+            return ProverResult::Limit(format!("configurations: {}", config.configurations));
+        }
+        if pipeline::expired() {
+            return ProverResult::Limit("time".to_owned());
+        }
+        if let Some(result) = ngrams.step(&local) {
+            return result;
+        }
+    }
+    ProverResult::Infinite(format!(
+        "ngram cps: radius {}, history {}, configurations {}",
+        radius,
+        config.history,
+        ngrams.locals.len()
+    ))
+}
+
+pub fn decide(machine: &Machine, config: &Config) -> ProverResult {
+    ProverResult::first_infinite(config.radii.iter().copied(), "no radius", |radius| {
+        decide_radius(machine, radius, config)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn radius() {
+        for (machine, summary) in [
+            ("1RB1RA_1LA---", "ngram cps: radius 2, history 0, configurations 9"),
+            ("1RB1LC_1LA0RB_1LB---", "ngram cps: radius 4, history 0, configurations 94"),
+        ] {
+            let result = decide(&Machine::from(machine), &Config::default());
+            assert_eq!(result.to_string(), format!("Infinite(\"{}\")", summary), "{}", machine);
+        }
+    }
+
+    #[test]
+    fn halting_reachable() {
+        // BB(3) champion
+        let result = decide(&Machine::from("1RB1RZ_1LB0RC_1LC1LA"), &Config::default());
+        assert_eq!(result.to_string(), "Limit(\"halting A1 reachable\")");
+    }
+}
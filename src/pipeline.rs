@@ -0,0 +1,241 @@
+// decider pipeline: stages are tried in order (cheapest first) until one decides the machine; a stage is a decider
+// with its parameters & optionally a time budget, e.g. `cycler(1000, 500) | ctl(4)@100ms | halt(100000)`
+// the time budget is cooperative: every decider polls `expired()` between bounded units of work (e.g. 4096 simulated
+// steps or one searched node) & gives up with `Limit("time")`, so a stage overruns its budget by at most one unit
+
+use std::{
+    cell::Cell,
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    ProverResult, backward_reasoning, bouncer, ctl, cycler, halting_segment,
+    machine::Machine,
+    mitm_wfar, ngram_cps, regex,
+    simulator::{Limits, simulate},
+    skelet_cps, translated_cycler,
+};
+
+/// one decider with its parameters; `Display`/`FromStr` is the stage syntax
+#[derive(Clone, Debug, PartialEq, Eq, parse_display::Display, parse_display::FromStr)]
+pub enum Decider {
+    /// direct simulation up to the step limit
+    #[display("halt({0})")]
+    Halt(u64),
+    /// exactly repeating configuration within (steps, space)
+    #[display("cycler({0}, {1})")]
+    Cycler(u64, usize),
+    /// configuration repeating shifted along the tape within (steps, space)
+    #[display("translated_cycler({0}, {1})")]
+    TranslatedCycler(u64, usize),
+    /// every backward run from a halting transition dies within the depth
+    #[display("backward_reasoning({0})")]
+    BackwardReasoning(usize),
+    /// finite automata reduction with up to this many DFA states
+    #[display("ctl({0})")]
+    Ctl(u8),
+    /// closed position set with segment lengths `1..=n`
+    #[display("skelet_cps({0})")]
+    SkeletCps(usize),
+    /// tape growing by repeated words, proven symbolically; records within (steps, space)
+    #[display("bouncer({0}, {1})")]
+    Bouncer(u64, usize),
+    /// backward search on segments of lengths `1..=n`
+    #[display("halting_segment({0})")]
+    HaltingSegment(usize),
+    /// n-gram closed position set with radii `1..=n`, cells remembering this many writing states
+    #[display("ngram_cps({0}, {1})")]
+    NgramCps(usize, u32),
+    /// weighted automata reduction with up to (DFA states, transition weight) on each side
+    #[display("mitm_wfar({0}, {1})")]
+    MitmWfar(usize, u32),
+    /// closed set of up to this many synthesized regex tape patterns
+    #[display("regex({0})")]
+    Regex(usize),
+}
+
+impl Decider {
+    pub fn prove(&self, machine: &Machine) -> ProverResult {
+        match self {
+            Decider::Halt(steps) => simulate(machine, Limits::steps(*steps)),
+            &Decider::Cycler(steps, space) => {
+                cycler::decide(machine, &cycler::Config { limits: Limits { steps, space: Some(space) } })
+            }
+            &Decider::TranslatedCycler(steps, space) => translated_cycler::decide(
+                machine,
+                &translated_cycler::Config { limits: Limits { steps, space: Some(space) } },
+            ),
+            &Decider::BackwardReasoning(depth) => {
+                backward_reasoning::decide(machine, &backward_reasoning::Config { depth, ..Default::default() })
+            }
+            &Decider::Ctl(dfa_states) => ctl::decide(machine, &ctl::Config { dfa_states, ..Default::default() }),
+            &Decider::Bouncer(steps, space) => bouncer::decide(
+                machine,
+                &bouncer::Config { limits: Limits { steps, space: Some(space) }, ..Default::default() },
+            ),
+            &Decider::HaltingSegment(len) => halting_segment::decide(
+                machine,
+                &halting_segment::Config { lengths: (1..=len).collect(), ..Default::default() },
+            ),
+            &Decider::MitmWfar(dfa_states, max_weight) => {
+                mitm_wfar::decide(machine, &mitm_wfar::Config { dfa_states, max_weight, ..Default::default() })
+            }
+            &Decider::NgramCps(radius, history) => ngram_cps::decide(
+                machine,
+                &ngram_cps::Config { radii: (1..=radius).collect(), history, ..Default::default() },
+            ),
+            &Decider::Regex(patterns) => regex::decide(machine, &regex::Config { patterns, ..Default::default() }),
+            &Decider::SkeletCps(len) => {
+                skelet_cps::decide(machine, &skelet_cps::Config { lengths: (1..=len).collect(), ..Default::default() })
+            }
+        }
+    }
+}
+
+thread_local! {
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// the time budget of the running stage is spent
+pub fn expired() -> bool {
+    DEADLINE.with(|deadline| matches!(deadline.get(), Some(deadline) if Instant::now() >= deadline))
+}
+
+/// `decider` or `decider@<n>ms`; the budget isn't preemptive, see the module header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stage {
+    pub decider: Decider,
+    pub time: Option<Duration>,
+}
+
+impl Stage {
+    pub fn prove(&self, machine: &Machine) -> ProverResult {
+        let deadline = self.time.map(|time| Instant::now() + time);
+        let prev = DEADLINE.with(|d| d.replace(deadline));
+        let ret = ProverResult::catch(|| self.decider.prove(machine));
+        DEADLINE.with(|d| d.set(prev));
+        ret
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.decider)?;
+        match self.time {
+            Some(time) => write!(f, "@{}ms", time.as_millis()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for Stage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (decider, time) = match s.trim().split_once('@') {
+            Some((decider, time)) => {
+                let ms = time.trim().strip_suffix("ms").and_then(|ms| ms.parse().ok());
+                let ms = ms.ok_or_else(|| format!("invalid time budget `{}`, expected e.g. `100ms`", time))?;
+                (decider, Some(Duration::from_millis(ms)))
+            }
+            None => (s, None),
+        };
+        let decider = decider.trim().parse().map_err(|_| format!("invalid decider `{}`", decider.trim()))?;
+        Ok(Stage { decider, time })
+    }
+}
+
+/// stages separated by `|`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pipeline {
+    pub stages: Vec<Stage>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        let deciders = [Decider::Cycler(1000, 500), Decider::TranslatedCycler(10_000, 1000), Decider::Halt(100_000)];
+        Pipeline { stages: deciders.iter().map(|decider| Stage { decider: decider.clone(), time: None }).collect() }
+    }
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stages: Vec<String> = self.stages.iter().map(|stage| stage.to_string()).collect();
+        write!(f, "{}", stages.join(" | "))
+    }
+}
+
+impl FromStr for Pipeline {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let stages = s.split('|').map(|stage| stage.parse()).collect::<Result<_, _>>()?;
+        Ok(Pipeline { stages })
+    }
+}
+
+/// final result of a pipeline
+#[derive(Clone, Debug)]
+pub struct Verdict {
+    pub result: ProverResult,
+    /// stage which decided the machine, or the last one tried; `None` for an empty pipeline
+    pub stage: Option<Stage>,
+}
+
+impl Pipeline {
+    /// result of the first stage which decides the machine, otherwise result of the last one
+    pub fn prove(&self, machine: &Machine) -> Verdict {
+        let mut ret = Verdict { result: ProverResult::Limit("no decider".to_owned()), stage: None };
+        for stage in &self.stages {
+            ret = Verdict { result: stage.prove(machine), stage: Some(stage.clone()) };
+            if let ProverResult::Halt { .. } | ProverResult::Infinite(_) = ret.result {
+                break;
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syntax() {
+        let pipeline: Pipeline = "cycler(1000, 500) | ctl(4)@100ms | halt(100000)".parse().unwrap();
+        assert_eq!(pipeline.stages.len(), 3);
+        assert_eq!(pipeline.stages[1], Stage { decider: Decider::Ctl(4), time: Some(Duration::from_millis(100)) });
+        assert_eq!(pipeline.to_string(), "cycler(1000, 500) | ctl(4)@100ms | halt(100000)");
+        assert!("ctl(4)@100".parse::<Stage>().is_err());
+        assert!("unknown(4)".parse::<Stage>().is_err());
+    }
+
+    #[test]
+    fn budget_stops_every_stage() {
+        // BB(5) champion, halts after 47 176 870 steps
+        let machine = Machine::parse("1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA").unwrap();
+        let deciders = [
+            Decider::Halt(u64::MAX),
+            Decider::Cycler(u64::MAX, 1_000_000),
+            Decider::TranslatedCycler(u64::MAX, 1_000_000),
+            Decider::BackwardReasoning(1_000_000),
+            Decider::Ctl(8),
+            Decider::SkeletCps(40),
+            Decider::Bouncer(u64::MAX, 1_000_000),
+            Decider::HaltingSegment(40),
+            Decider::NgramCps(12, 4),
+            Decider::MitmWfar(5, 4),
+            Decider::Regex(1_000_000),
+        ];
+        for decider in deciders.iter().cloned() {
+            let stage = Stage { decider, time: Some(Duration::from_millis(50)) };
+            let start = Instant::now();
+            let result = stage.prove(&machine);
+            assert!(start.elapsed() < Duration::from_secs(1), "{}: {:?}", stage, start.elapsed());
+            assert!(matches!(result, ProverResult::Limit(_)), "{}: {}", stage, result);
+        }
+        assert!(!expired());
+    }
+}
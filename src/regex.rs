@@ -0,0 +1,970 @@
+// symbolic tape patterns: a set of patterns is closed if every successor of every pattern matches some pattern of
+// the set; a closed set containing the initial configuration `A: 0@  0  0@` without a halting transition proves the
+// machine never halts; such a set can be synthesized by generalizing unmatched successors into repetitions
+// a successor is covered if it's included in the union of patterns, decided exactly on finite automata; the
+// backtracking matcher only proposes candidate patterns, as it may report false matches
+// quick ~rewrite of Frans's code; enhanced so it should handle (a*b*)* to simulate (a|b)*
+// https://github.com/FransFaase/SymbolicTM
+
+use anyhow::{Context, Result, bail};
+use hashbrown::{HashMap, HashSet};
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    ProverResult,
+    machine::{Machine, Transition},
+    pipeline,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, parse_display::Display, parse_display::FromStr)]
+pub enum Rep {
+    #[display("+")]
+    NonZero,
+    #[display("*")]
+    Any,
+    #[display("@")]
+    Infinite,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Symbol(u8),
+    Any,
+    Rep(Tape, Rep),
+}
+
+impl Item {
+    fn max_symbol(&self) -> u8 {
+        match self {
+            Item::Symbol(s) => *s,
+            Item::Any => 0,
+            Item::Rep(tape, _) => tape.iter().map(Item::max_symbol).max().unwrap_or(0),
+        }
+    }
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>, orient: usize) -> std::fmt::Result {
+        match self {
+            Item::Symbol(s) => write!(f, "{}", (s + b'0') as char),
+            Item::Any => write!(f, "."),
+            Item::Rep(tape, rep) => {
+                if tape.len() > 1 {
+                    write!(f, "(")?;
+                }
+                fmt_tape(tape, f, orient)?;
+                if tape.len() > 1 {
+                    write!(f, ")")?;
+                }
+                write!(f, "{}", rep)
+            }
+        }
+    }
+}
+
+pub type Tape = Vec<Item>;
+pub type TapeRef<'a> = &'a [Item];
+
+fn fmt_tape(t: TapeRef<'_>, f: &mut std::fmt::Formatter<'_>, orient: usize) -> std::fmt::Result {
+    let mut it: Box<dyn Iterator<Item = _>> = if orient == 1 { Box::new(t.iter().rev()) } else { Box::new(t.iter()) };
+    it.try_for_each(|i| i.fmt(f, orient))
+}
+
+#[allow(unused)]
+struct P<'a>(TapeRef<'a>);
+impl<'a> std::fmt::Display for P<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_tape(self.0, f, 0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    pub state: u8,
+    pub symbol: u8, // == head
+    /// 0 == left tape; item closes to head is _last_ => right tape needs to be reversed in parsing & printing
+    pub tape: [Tape; 2],
+}
+
+impl Pattern {
+    /// `A: 0@  0  0@`, the blank tape in the initial state
+    pub fn is_start(&self) -> bool {
+        let blank = [Item::Rep(vec![Item::Symbol(0)], Rep::Infinite)];
+        self.state == 0 && self.symbol == 0 && self.tape.iter().all(|t| t[..] == blank)
+    }
+
+    pub fn max_symbol(&self) -> u8 {
+        self.tape.iter().flatten().map(Item::max_symbol).fold(self.symbol, u8::max)
+    }
+}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", (self.state + b'A') as char)?;
+        fmt_tape(&self.tape[0], f, 0)?;
+        write!(f, "  {}  ", (self.symbol + b'0') as char)?;
+        fmt_tape(&self.tape[1], f, 1)
+    }
+}
+
+impl std::str::FromStr for Pattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pattern = pattern_parser::pattern(s)?;
+        // let original = s.split_whitespace().join(" ");
+        // let parsed = pattern.to_string().replace("  ", " ");
+        // assert_eq!(original, parsed);
+        Ok(pattern)
+    }
+}
+
+peg::parser! {
+    grammar pattern_parser() for str {
+        rule whitespace() = quiet!{[' ' | '\t']+}
+
+        rule symbol() -> u8 = s:$(['0'..='9']) { s.parse().unwrap() }
+
+        rule item(orient: usize) -> Item = precedence!{
+            s:symbol() r:$(['+'|'*'|'@']?) { let item = Item::Symbol(s); if r.is_empty() { item } else { Item::Rep(vec![item], r.parse::<Rep>().unwrap()) } }
+            "." r:$(['+'|'*'|'@']?) { let item = Item::Any; if r.is_empty() { item } else { Item::Rep(vec![item], r.parse::<Rep>().unwrap()) } }
+            "(" t:tape(orient) ")" r:$(['+'|'*'|'@']) { Item::Rep(t, r.parse::<Rep>().unwrap()) }
+        }
+
+        pub rule tape(orient: usize) -> Tape
+            = items:(item(orient)+) { let mut items = items; if orient == 1 { items.reverse() }; items }
+
+        pub rule pattern() -> Pattern
+            = state:(['A'..='Z']) ":" whitespace() left:tape(0) whitespace() s:symbol() whitespace() right:tape(1) {
+                Pattern { state: state as u8 - b'A', symbol: s, tape: [left, right] }
+            }
+    }
+}
+
+/// @returns `Some(a.remainder)` if `partial` && prefix of `a` matches `b`
+/// @returns `Some(&[])` if !partial && `a` matches `b`
+fn tmatch<'a, 'b>(a: TapeRef<'a>, b: TapeRef<'b>, partial: bool, aplus: bool) -> Option<TapeRef<'a>> {
+    // println!("\t{} -> {}", P(a), P(b));
+
+    // `->` == matches; `'` == `X -> X'`
+    let ret = match (a.split_last(), b.split_last()) {
+        // we want hard return for end conditions - no need to backtrack
+        (_, None) if partial => Some(a), // `b` could be nested pattern and ends early
+        (None, None) if !partial => return Some(&[]),
+        (None, _) => return None,
+        // `_A -> _A'`
+        (Some((Item::Symbol(sa), aa)), Some((Item::Symbol(sb), bb))) if sa == sb => tmatch(aa, bb, partial, false),
+        (Some((Item::Any | Item::Symbol(_), aa)), Some((Item::Any, bb))) => tmatch(aa, bb, partial, false),
+        // `_A[eq_rep] -> _A'[eq_rep]`
+        // `_A+ -> _A'[+*@]`
+        (Some((Item::Rep(ta, ra), aa)), Some((Item::Rep(tb, rb), bb)))
+            if (ra == rb || *ra == Rep::NonZero || (*ra == Rep::Any && aplus))
+                && tmatch(ta, tb, false, false).is_some() =>
+        {
+            tmatch(aa, bb, partial, false)
+        }
+        // `_ -> _B*`
+        (Some(_), Some((Item::Rep(_, Rep::Any), bb))) => tmatch(a, bb, partial, false),
+        _ => None,
+    };
+    if ret.is_some() {
+        return ret;
+    }
+    if let Some((Item::Rep(tb, _), bb)) = b.split_last() {
+        // println!("before");
+        return tmatch(a, tb, true, false).and_then(|xa| {
+            if
+            /* a.len() > 0 && */
+            a.len() == xa.len() {
+                return None;
+            }
+
+            // `_(AB)*AB -> _(A'B')+`
+            // println!("after");
+            let ret = tmatch(xa, b, partial, true);
+            // println!("...{}", ret.is_some());
+            if ret.is_some() {
+                return ret;
+            }
+            // `_AB -> _(A'B')[+*@]`
+            tmatch(xa, bb, partial, false)
+        });
+    }
+    None
+}
+
+fn can_absorb(a: TapeRef<'_>, b: TapeRef<'_>) -> bool {
+    // println!("can_absorb {} -> {}", P(a), P(b));
+
+    // `A -> (B)[*+@]`
+    if let Some((Item::Rep(tb, rb), bb)) = b.split_last() {
+        // `A -> B` || `can_absorb(A, B)` || `A -> CB* & can_absorb(A, C)`
+        tmatch(a, tb, false, false).is_some() || can_absorb(a, tb) || (*rb == Rep::Any && can_absorb(a, bb))
+    } else {
+        false
+    }
+}
+
+fn tape_match(a: TapeRef<'_>, b: TapeRef<'_>) -> bool {
+    // println!("{} -> {}", P(a), P(b));
+
+    if tmatch(a, b, false, false).is_some() {
+        return true;
+    }
+
+    (1..a.len()).rev().any(|s| {
+        // remove absorbable part of `a` ~ `(AB+)*B -> (AB+)*`
+        let (a1, a2) = a.split_at(s);
+
+        can_absorb(a2, b) && tmatch(a1, b, false, false).is_some()
+    })
+}
+
+/// backtracking matcher, it may report false matches
+/// @returns patterns likely covering `pat`
+fn candidates(pat: &Pattern, pats: &Patterns) -> Option<Vec<usize>> {
+    let found = pats.iter().position(|p| {
+        pat.state == p.state
+            && pat.symbol == p.symbol
+            && pat.tape.iter().zip(p.tape.iter()).all(|(tape, t)| tape_match(tape, t))
+    });
+    if let Some(idx) = found {
+        return Some(vec![idx]);
+    }
+
+    // `BA* -> B'A+ && B'`
+    for tidx in 0..=1 {
+        if let Some(Item::Rep(_, Rep::Any)) = pat.tape[tidx].last() {
+            let mut pat = pat.clone();
+            let mut last = pat.tape[tidx].pop().unwrap();
+            if let Some(mut short) = candidates(&pat, pats) {
+                if let Item::Rep(_, r) = &mut last {
+                    *r = Rep::NonZero
+                } else {
+                    unreachable!()
+                };
+                pat.tape[tidx].push(last);
+                if let Some(long) = candidates(&pat, pats) {
+                    short.extend(long);
+                    return Some(short);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// max number of subset states explored by `included` on one side
+const SUBSETS: usize = 10_000;
+
+/// sorted set of NFA states
+type States = Vec<usize>;
+
+/// nondeterministic automaton of a tape without its far end `X@`, reading the tape in order
+struct Nfa {
+    eps: Vec<Vec<usize>>,
+    trans: Vec<Vec<(u8, usize)>>,
+    /// initial states, closed under ε
+    start: States,
+    accept: usize,
+}
+
+impl Nfa {
+    fn state(&mut self) -> usize {
+        self.eps.push(vec![]);
+        self.trans.push(vec![]);
+        self.eps.len() - 1
+    }
+
+    /// @returns the state after `tape` read from `from`, `None` for a nested `@`
+    fn build(&mut self, tape: TapeRef<'_>, symbols: u8, mut from: usize) -> Option<usize> {
+        for item in tape {
+            from = match item {
+                Item::Symbol(s) => {
+                    let to = self.state();
+                    self.trans[from].push((*s, to));
+                    to
+                }
+                Item::Any => {
+                    let to = self.state();
+                    self.trans[from].extend((0..symbols).map(|s| (s, to)));
+                    to
+                }
+                // fresh entry & exit states keep the loop away from neighbouring items
+                Item::Rep(w, rep @ (Rep::NonZero | Rep::Any)) => {
+                    let (entry, exit) = (self.state(), self.state());
+                    self.eps[from].push(entry);
+                    let end = self.build(w, symbols, entry)?;
+                    self.eps[end].push(entry);
+                    self.eps[if *rep == Rep::Any { entry } else { end }].push(exit);
+                    exit
+                }
+                Item::Rep(_, Rep::Infinite) => return None,
+            }
+        }
+        Some(from)
+    }
+
+    fn closure(&self, states: impl IntoIterator<Item = usize>) -> States {
+        let mut stack: Vec<usize> = states.into_iter().collect();
+        let mut ret = stack.clone();
+        while let Some(state) = stack.pop() {
+            for &next in &self.eps[state] {
+                if !ret.contains(&next) {
+                    ret.push(next);
+                    stack.push(next);
+                }
+            }
+        }
+        ret.sort_unstable();
+        ret.dedup();
+        ret
+    }
+
+    fn step(&self, states: &[usize], symbol: u8) -> States {
+        self.closure(states.iter().flat_map(|&s| &self.trans[s]).filter(|(sym, _)| *sym == symbol).map(|(_, to)| *to))
+    }
+
+    fn accepts(&self, states: &[usize]) -> bool {
+        states.binary_search(&self.accept).is_ok()
+    }
+
+    /// `X@rest` is compiled to `X*rest ∪ (X*)⁻¹rest` with `X` reduced to its primitive root, as `X@r == X@s` iff
+    /// `r ∈ X*s` or `s ∈ X*r`; the root `uv` is rotated to its least rotation `vu` by `(uv)@ == (vu)@v`, so rotated
+    /// far ends like `(10)@1 == (01)@` share a root; only a far end of plain symbols is supported
+    /// @returns (least rotation of the root of `X`, automaton of the rest)
+    fn tape(tape: TapeRef<'_>, symbols: u8) -> Option<(Vec<u8>, Nfa)> {
+        let (far, rest) = tape.split_first()?;
+        let Item::Rep(x, Rep::Infinite) = far else { return None };
+        let x: Vec<u8> =
+            x.iter().map(|i| if let Item::Symbol(s) = i { Some(*s) } else { None }).collect::<Option<_>>()?;
+        let root = (1..=x.len()).find(|&p| x.chunks(p).all(|c| c == &x[..p]))?;
+        let rotated = |k: usize| [&x[k..root], &x[..k]].concat();
+        let k = (1..=root).min_by_key(|&k| rotated(k))?;
+        let rest: Tape = x[k..root].iter().map(|s| Item::Symbol(*s)).chain(rest.iter().cloned()).collect();
+        let x = rotated(k);
+
+        let mut nfa = Nfa { eps: vec![vec![]], trans: vec![vec![]], start: vec![], accept: 0 };
+        let star = Item::Rep(x.iter().map(|s| Item::Symbol(*s)).collect(), Rep::Any);
+        let mid = nfa.build(std::slice::from_ref(&star), symbols, 0)?;
+        nfa.accept = nfa.build(&rest, symbols, mid)?;
+
+        let mut start = nfa.closure(std::iter::once(0));
+        let mut seen = HashSet::new();
+        let mut quotient = nfa.closure(std::iter::once(mid));
+        while seen.insert(quotient.clone()) {
+            start.extend(&quotient);
+            quotient = x.iter().fold(quotient, |states, &s| nfa.step(&states, s));
+        }
+        start.sort_unstable();
+        start.dedup();
+        nfa.start = start;
+        Some((x, nfa))
+    }
+}
+
+/// subset construction over `nfa` running together with `others`
+/// @returns `None` if more than `SUBSETS` subset states, otherwise for every word accepted by `nfa`, indices of
+/// `others` accepting it too
+fn accepted_together(nfa: &Nfa, others: &[&Nfa], symbols: u8) -> Option<HashSet<Vec<usize>>> {
+    let mut ret = HashSet::new();
+    let init = (nfa.start.clone(), others.iter().map(|o| o.start.clone()).collect::<Vec<_>>());
+    let mut stack = vec![init.clone()];
+    let mut visited: HashSet<_> = std::iter::once(init).collect();
+    while let Some((states, other_states)) = stack.pop() {
+        if visited.len() > SUBSETS || pipeline::expired() {
+            return None;
+        }
+        if nfa.accepts(&states) {
+            ret.insert((0..others.len()).filter(|&i| others[i].accepts(&other_states[i])).collect());
+        }
+        for symbol in 0..symbols {
+            let next = nfa.step(&states, symbol);
+            if next.is_empty() {
+                continue;
+            }
+            let next = (next, others.iter().zip(&other_states).map(|(o, s)| o.step(s, symbol)).collect());
+            if visited.insert(next.clone()) {
+                stack.push(next);
+            }
+        }
+    }
+    Some(ret)
+}
+
+/// decides exactly whether `pat` is included in the union of `pats`: for every left tape of `pat`, its right tapes
+/// must be included in the union of right tapes of the patterns accepting that left tape
+/// @returns the covering patterns, `Undecided` with all patterns of the same state & symbol above `SUBSETS`, out of
+/// time or when a far end isn't plain symbols
+fn included<'a>(pat: &Pattern, pats: impl IntoIterator<Item = (usize, &'a Pattern)>, symbols: u8) -> Outcome {
+    let pats: Vec<(usize, &Pattern)> =
+        pats.into_iter().filter(|(_, p)| p.state == pat.state && p.symbol == pat.symbol).collect();
+    let undecided = || Outcome::Undecided(pats.iter().map(|(idx, _)| *idx).collect());
+    let [left, right] = &pat.tape;
+    let (Some((left_root, left)), Some((right_root, right))) = (Nfa::tape(left, symbols), Nfa::tape(right, symbols))
+    else {
+        return undecided();
+    };
+    // other far ends can't cover `pat`, unsupported ones may
+    let mut unsupported = false;
+    let candidates: Vec<(usize, Nfa, Nfa)> = pats
+        .iter()
+        .filter_map(|&(idx, p)| match (Nfa::tape(&p.tape[0], symbols), Nfa::tape(&p.tape[1], symbols)) {
+            (Some((l_root, l)), Some((r_root, r))) => {
+                (l_root == left_root && r_root == right_root).then_some((idx, l, r))
+            }
+            _ => {
+                unsupported = true;
+                None
+            }
+        })
+        .collect();
+
+    let lefts: Vec<&Nfa> = candidates.iter().map(|(_, l, _)| l).collect();
+    let Some(sets) = accepted_together(&left, &lefts, symbols) else { return undecided() };
+    for set in &sets {
+        let rights: Vec<&Nfa> = set.iter().map(|&i| &candidates[i].2).collect();
+        let Some(uncovered) = accepted_together(&right, &rights, symbols) else { return undecided() };
+        if uncovered.iter().any(|accepting| accepting.is_empty()) {
+            return if unsupported { undecided() } else { Outcome::NotMatched };
+        }
+    }
+    let idxs: Vec<usize> = sets.iter().flatten().sorted().dedup().map(|&i| candidates[i].0).collect();
+    Outcome::Covered(match idxs[..] {
+        [idx] => Cover::Subsumed(idx),
+        _ => Cover::Union(idxs),
+    })
+}
+
+/// equal pattern, otherwise exact inclusion: first in the candidates of the backtracking matcher, then in all patterns
+fn covers(pat: &Pattern, pats: &Patterns, symbols: u8) -> Outcome {
+    if let Some(idx) = pats.iter().position(|p| pat == p) {
+        return Outcome::Covered(Cover::Exact(idx));
+    }
+    if let Some(idxs) = candidates(pat, pats) {
+        if let covered @ Outcome::Covered(_) = included(pat, idxs.iter().map(|&idx| (idx, &pats[idx])), symbols) {
+            return covered;
+        }
+    }
+    included(pat, pats.iter().enumerate(), symbols)
+}
+
+/// matches `pat` & records it as a successor
+fn successor(pat: &Pattern, pats: &Patterns, symbols: u8, successors: &mut Vec<Successor>) -> bool {
+    let outcome = covers(pat, pats, symbols);
+    let matched = matches!(outcome, Outcome::Covered(_));
+    successors.push(Successor { pattern: pat.clone(), outcome });
+    matched
+}
+
+/// explores all possibilities how to pop symbol from the new pattern's tape, pushes new symbol & tests if these new patterns are already in set
+/// every successor is recorded, even after an unmatched one
+fn explore(
+    mut pat: Pattern,
+    trans: Transition,
+    symbols: u8,
+    pats: &Patterns,
+    successors: &mut Vec<Successor>,
+    mut orig_pat_len: Option<usize>,
+) -> Result<bool> {
+    let tidx = trans.head.orient as usize;
+    let mut closed = true;
+    loop {
+        // println!("{i} {orig_pat_len:?} {}", P(&pat.tape[tidx]));
+        match pat.tape[tidx].last_mut().context("empty tape")? {
+            // `_0` || `_1`
+            Item::Symbol(s) => {
+                pat.symbol = *s;
+                pat.tape[tidx].pop();
+                break Ok(successor(&pat, pats, symbols, successors) && closed);
+            }
+            // `_. -> _0 && _1`
+            Item::Any => {
+                pat.tape[tidx].pop();
+                for s in 0..symbols {
+                    pat.symbol = s;
+                    closed &= successor(&pat, pats, symbols, successors);
+                }
+                break Ok(closed);
+            }
+            // `A+ -> A*A` || `A@ -> A@A`
+            Item::Rep(tape, rep @ (Rep::NonZero | Rep::Infinite)) => {
+                let tape = tape.clone();
+                if *rep == Rep::NonZero {
+                    *rep = Rep::Any;
+                }
+                pat.tape[tidx].extend(tape);
+                continue;
+            }
+            // `_BA* -> _B && _BA*A`
+            Item::Rep(tape, Rep::Any) => {
+                let tape = tape.clone();
+                // this prevents recursive testing of original pattern in case of nested *
+                // 0@(0*(11)*)*
+                //     0@
+                //         0@0
+                //     0@(0*(11)*)*0*(11)*
+                //         0@(0*(11)*)*0*
+                //             0@(0*(11)*)*
+                if !orig_pat_len.map(|l| l == pat.tape[tidx].len() - 1).unwrap_or(false) {
+                    let mut pat_short = pat.clone(); // TODO: optimize
+                    pat_short.tape[tidx].pop();
+                    closed &= explore(pat_short, trans, symbols, pats, successors, orig_pat_len)?;
+                    orig_pat_len = Some(pat.tape[tidx].len())
+                }
+                pat.tape[tidx].extend(tape);
+                continue;
+            }
+        }
+    }
+}
+
+pub type Patterns = Vec<Pattern>;
+/// covering pattern -> (exact match, covered pattern) for every successor it covers
+pub type Matched = HashMap<usize, Vec<(bool, usize)>>;
+
+/// how a successor is covered by the pattern set
+#[derive(Clone, Debug, Serialize)]
+pub enum Cover {
+    /// equal to the pattern with this index
+    Exact(usize),
+    /// included in the pattern with this index, decided on automata
+    Subsumed(usize),
+    /// included in the union of the patterns with these indices, decided on automata
+    Union(Vec<usize>),
+}
+
+impl std::fmt::Display for Cover {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cover::Exact(idx) => write!(f, "{idx} - exact match"),
+            Cover::Subsumed(idx) => write!(f, "{idx} - match"),
+            Cover::Union(idxs) => write!(f, "{} - union", idxs.iter().join(", ")),
+        }
+    }
+}
+
+impl Cover {
+    /// records covering patterns of successors of pattern `from`
+    fn record(&self, from: usize, matched_from: &mut Matched) {
+        match self {
+            Cover::Exact(idx) => matched_from.entry(*idx).or_default().push((true, from)),
+            Cover::Subsumed(idx) => matched_from.entry(*idx).or_default().push((false, from)),
+            Cover::Union(idxs) => idxs.iter().for_each(|idx| matched_from.entry(*idx).or_default().push((false, from))),
+        }
+    }
+}
+
+/// how a successor is matched against the pattern set
+#[derive(Clone, Debug, Serialize)]
+pub enum Outcome {
+    Covered(Cover),
+    /// inclusion in the patterns with these indices isn't decided, see `included`
+    Undecided(Vec<usize>),
+    NotMatched,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Successor {
+    #[serde(serialize_with = "display")]
+    pub pattern: Pattern,
+    pub outcome: Outcome,
+}
+
+impl std::fmt::Display for Successor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.outcome {
+            Outcome::Covered(cover) => write!(f, "{} -> {}", self.pattern, cover),
+            Outcome::Undecided(idxs) => write!(f, "{} -> ??? UNDECIDED ??? ({})", self.pattern, idxs.iter().join(", ")),
+            Outcome::NotMatched => write!(f, "{} -> !!! NOT MATCHED !!!", self.pattern),
+        }
+    }
+}
+
+/// successors of one input pattern
+#[derive(Clone, Debug, Serialize)]
+pub struct PatternReport {
+    #[serde(serialize_with = "display")]
+    pub pattern: Pattern,
+    #[serde(serialize_with = "display")]
+    pub transition: Transition,
+    pub successors: Vec<Successor>,
+}
+
+/// outcome of `verify`
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    /// in the order of the input patterns
+    pub patterns: Vec<PatternReport>,
+    /// every successor of every pattern is matched by some pattern
+    pub closed: bool,
+    #[serde(skip)]
+    pub matched_from: Matched,
+    /// patterns covering no successor of any pattern, except the start pattern & patterns of undecided successors;
+    /// `explore` records all successors, so this holds for non-closed reports too
+    pub unused: Vec<usize>,
+    /// number of successors whose inclusion isn't decided, they make the report not closed without being unmatched
+    pub undecided: usize,
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, report) in self.patterns.iter().enumerate() {
+            writeln!(f, "{idx:<4}{} ({}):", report.pattern, report.transition)?;
+            report.successors.iter().try_for_each(|successor| writeln!(f, "        {successor}"))?;
+        }
+        for &idx in &self.unused {
+            writeln!(f, "unused pattern: {idx}\t{}", self.patterns[idx].pattern)?;
+        }
+        write!(f, "{}", if self.closed { "closed" } else { "NOT closed" })?;
+        if self.undecided > 0 {
+            write!(f, " ({} undecided)", self.undecided)?;
+        }
+        Ok(())
+    }
+}
+
+fn display<T: std::fmt::Display, S: serde::Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// drops unused patterns & verifies the rest until no pattern is unused or the patterns aren't closed
+/// @returns the remaining patterns with their report
+pub fn minimize(machine: &Machine, patterns: &[Pattern]) -> Result<(Patterns, Report)> {
+    let mut patterns = patterns.to_vec();
+    loop {
+        let report = verify(machine, &patterns)?;
+        if !report.closed || report.unused.is_empty() {
+            return Ok((patterns, report));
+        }
+        patterns =
+            patterns.into_iter().enumerate().filter(|(idx, _)| !report.unused.contains(idx)).map(|(_, p)| p).collect();
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// max number of synthesized patterns
+    pub patterns: usize,
+    /// max length of a repeated word turned into `(word)+`
+    pub word: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { patterns: 100, word: 2 }
+    }
+}
+
+/// over-approximates the tape by repetitions: `ww -> w+`, `w+w -> w+`, `ww+ -> w+`, `w*w -> w+`, `w*w* -> w*` &
+/// `0@0 -> 0@`; every rewrite keeps all words of the tape
+fn generalize_tape(tape: &mut Tape, word: usize) {
+    let rep_of = |item: &Item| match item {
+        Item::Rep(w, Rep::NonZero | Rep::Any | Rep::Infinite) => Some(w.clone()),
+        _ => None,
+    };
+    'again: loop {
+        for i in 0..tape.len() {
+            // `w+w`, `w*w` & `w@w` (far end only)
+            if let Some(w) = rep_of(&tape[i]) {
+                let (l, r) = tape.split_at(i + 1);
+                if r.starts_with(&w) {
+                    if let Item::Rep(_, rep @ Rep::Any) = &mut tape[i] {
+                        *rep = Rep::NonZero;
+                    }
+                    tape.drain(i + 1..i + 1 + w.len());
+                    continue 'again;
+                }
+                // `ww+`
+                if i >= w.len() && l[i - w.len()..i] == w[..] && !matches!(tape[i], Item::Rep(_, Rep::Infinite)) {
+                    if let Item::Rep(_, rep) = &mut tape[i] {
+                        *rep = Rep::NonZero;
+                    }
+                    tape.drain(i - w.len()..i);
+                    continue 'again;
+                }
+            }
+            // `ww`
+            for len in 1..=word {
+                if i + 2 * len <= tape.len() && tape[i..i + len] == tape[i + len..i + 2 * len] {
+                    // `w+w+ -> w+` & `w*w* -> w*`
+                    let (w, rep) = match &tape[i..i + len] {
+                        [Item::Rep(w, rep)] => (w.clone(), *rep),
+                        w => (w.to_vec(), Rep::NonZero),
+                    };
+                    tape.splice(i..i + 2 * len, std::iter::once(Item::Rep(w, rep)));
+                    continue 'again;
+                }
+            }
+        }
+        break;
+    }
+}
+
+fn generalize(pat: &Pattern, word: usize) -> Pattern {
+    let mut pat = pat.clone();
+    pat.tape.iter_mut().for_each(|tape| generalize_tape(tape, word));
+    pat
+}
+
+/// `w+` becomes `w*` where `patterns` has the pattern without it, so the widened pattern covers both
+fn widen(pat: &Pattern, patterns: &Patterns) -> Pattern {
+    let mut pat = pat.clone();
+    for side in 0..2 {
+        for i in 0..pat.tape[side].len() {
+            if let Item::Rep(_, Rep::NonZero) = pat.tape[side][i] {
+                let mut without = pat.clone();
+                without.tape[side].remove(i);
+                if patterns.contains(&without) {
+                    if let Item::Rep(_, rep) = &mut pat.tape[side][i] {
+                        *rep = Rep::Any;
+                    }
+                }
+            }
+        }
+    }
+    pat
+}
+
+/// builds a closed pattern set from the start pattern: unmatched successors are generalized & added until closed
+/// @returns closed patterns with their report
+pub fn synthesize(machine: &Machine, config: &Config) -> std::result::Result<(Patterns, Report), ProverResult> {
+    let mut patterns: Patterns = vec!["A: 0@  0  0@".parse().unwrap()];
+    loop {
+        if pipeline::expired() {
+            return Err(ProverResult::Limit("time".to_owned()));
+        }
+        let report = verify(machine, &patterns).map_err(|e| ProverResult::Limit(e.to_string()))?;
+        if report.closed {
+            return Ok((patterns, report));
+        }
+
+        let mut added = false;
+        let successors = || report.patterns.iter().flat_map(|r| &r.successors);
+        let unmatched = successors().filter(|s| matches!(s.outcome, Outcome::NotMatched));
+        for successor in unmatched {
+            match machine.get_transition(successor.pattern.symbol, successor.pattern.state) {
+                Some(trans) if trans.head.state < machine.states() => {}
+                _ => return Err(ProverResult::Limit(format!("halting successor: {}", successor.pattern))),
+            }
+            // a generalization in the set may still miss the successor
+            let generalized = generalize(&successor.pattern, config.word);
+            let pat = if !patterns.contains(&generalized) {
+                generalized
+            } else {
+                let widened = widen(&generalized, &patterns);
+                if patterns.contains(&widened) { successor.pattern.clone() } else { widened }
+            };
+            if !patterns.contains(&pat) {
+                if patterns.len() >= config.patterns {
+                    return Err(ProverResult::Limit(format!("patterns: {}", config.patterns)));
+                }
+                patterns.push(pat);
+                added = true;
+            }
+        }
+        if !added {
+            if let Some(successor) = successors().find(|s| matches!(s.outcome, Outcome::Undecided(_))) {
+                return Err(ProverResult::Limit(format!("undecided successor: {}", successor.pattern)));
+            }
+            return Err(ProverResult::Limit("no generalization".to_owned()));
+        }
+    }
+}
+
+/// covers are exact, so a closed synthesized set proves the machine
+pub fn decide(machine: &Machine, config: &Config) -> ProverResult {
+    match synthesize(machine, config) {
+        Ok((patterns, _)) => ProverResult::Infinite(format!("regex: {} patterns", patterns.len())),
+        Err(result) => result,
+    }
+}
+
+/// explores successors of all `patterns` & matches them against `patterns`
+pub fn verify(machine: &Machine, patterns: &[Pattern]) -> Result<Report> {
+    if let Some((idx, pat)) = patterns.iter().find_position(|p| p.max_symbol() >= machine.symbols()) {
+        bail!("pattern {idx} uses symbols missing in {}-symbol machine: {pat}", machine.symbols());
+    }
+
+    let patterns: Patterns = patterns.to_vec();
+    let mut report =
+        Report { patterns: vec![], closed: true, matched_from: HashMap::new(), unused: vec![], undecided: 0 };
+    let mut possibly_used: HashSet<usize> = HashSet::new();
+    for (idx, pat) in patterns.iter().enumerate() {
+        if pipeline::expired() {
+            bail!("time");
+        }
+        if pat.state >= machine.states() {
+            bail!("pattern {idx} uses state missing in {}-state machine: {pat}", machine.states());
+        }
+        let trans = machine
+            .get_transition(pat.symbol, pat.state)
+            .with_context(|| format!("undefined transition in pattern {idx}: {pat}"))?;
+        if trans.head.state >= machine.states() {
+            bail!("halting transition in pattern {idx}: {pat}");
+        }
+
+        let mut next = pat.clone();
+        next.state = trans.head.state;
+        next.tape[1 - trans.head.orient as usize].push(Item::Symbol(trans.symbol));
+        let mut successors = vec![];
+        if !explore(next, trans, machine.symbols(), &patterns, &mut successors, None)? {
+            report.closed = false;
+        }
+        for successor in &successors {
+            match &successor.outcome {
+                Outcome::Covered(cover) => cover.record(idx, &mut report.matched_from),
+                Outcome::Undecided(idxs) => {
+                    report.undecided += 1;
+                    possibly_used.extend(idxs);
+                }
+                Outcome::NotMatched => {}
+            }
+        }
+        report.patterns.push(PatternReport { pattern: pat.clone(), transition: trans, successors });
+    }
+
+    report.unused = (0..patterns.len())
+        .filter(|idx| {
+            !report.matched_from.contains_key(idx) && !possibly_used.contains(idx) && !patterns[*idx].is_start()
+        })
+        .collect();
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tape(s: &str) -> Tape {
+        pattern_parser::tape(s, 0).unwrap()
+    }
+
+    fn patterns(patterns: &[&str]) -> Patterns {
+        patterns.iter().map(|p| p.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn verify_reports_all_successors() {
+        let machine = Machine::parse("1RB---_1LA1LA").unwrap();
+        let report = verify(&machine, &patterns(&["A: 0@  0  .0@", "B: 0@1  1  0@"])).unwrap();
+        assert!(!report.closed);
+        let successors: Vec<_> = report.patterns[0].successors.iter().map(|s| s.pattern.to_string()).collect();
+        assert_eq!(successors, ["B: 0@1  0  0@", "B: 0@1  1  0@"]);
+        assert!(matches!(report.patterns[0].successors[0].outcome, Outcome::NotMatched));
+        assert!(matches!(report.patterns[0].successors[1].outcome, Outcome::Covered(_)));
+        assert_eq!(report.unused, [0]);
+    }
+
+    #[test]
+    fn minimize_drops_unused() {
+        let machine = Machine::parse("1RA---").unwrap();
+        let all = patterns(&["A: 0@  0  0@", "A: 0@1*  0  0@", "A: 0@1+  0  0@", "A: 0@1+1  0  0@"]);
+        let report = verify(&machine, &all).unwrap();
+        assert!(report.closed);
+        assert_eq!(report.unused, [2]);
+        let (minimized, report) = minimize(&machine, &all).unwrap();
+        assert!(report.closed);
+        assert_eq!(minimized, all[..2]);
+    }
+
+    #[test]
+    fn generalize_keeps_words() {
+        let generalized = |s: &str| {
+            let mut t = tape(s);
+            generalize_tape(&mut t, 2);
+            t
+        };
+        assert_eq!(generalized("0@1*1*"), tape("0@1*"));
+        assert_eq!(generalized("0@1+1+"), tape("0@1+"));
+        assert_eq!(generalized("0@1*1"), tape("0@1+"));
+        assert_eq!(generalized("0@11"), tape("0@1+"));
+        assert_eq!(generalized("0@0"), tape("0@"));
+        assert_eq!(generalized("0@1010"), tape("0@(10)+"));
+    }
+
+    #[test]
+    fn widen_introduces_any() {
+        let pat: Pattern = "A: 0@1+  0  0@".parse().unwrap();
+        assert_eq!(widen(&pat, &patterns(&["B: 0@  0  0@"])).to_string(), "A: 0@1+  0  0@");
+        assert_eq!(widen(&pat, &patterns(&["A: 0@  0  0@"])).to_string(), "A: 0@1*  0  0@");
+    }
+
+    #[test]
+    fn false_match_rejected() {
+        // `0@1*0` isn't included in `0@1*`, though the backtracking matcher absorbs the `0`
+        let pats = patterns(&["A: 0@1*  1  0@"]);
+        let pat: Pattern = "A: 0@1*0  1  0@".parse().unwrap();
+        assert_eq!(candidates(&pat, &pats), Some(vec![0]));
+        assert!(matches!(covers(&pat, &pats, 2), Outcome::NotMatched));
+        assert!(matches!(covers(&"A: 0@1+  1  0@".parse().unwrap(), &pats, 2), Outcome::Covered(Cover::Subsumed(0))));
+    }
+
+    #[test]
+    fn union_cover() {
+        let pats = patterns(&["A: 0@(11)*  0  0@", "A: 0@1(11)*  0  0@"]);
+        let pat: Pattern = "A: 0@1*  0  0@".parse().unwrap();
+        assert!(matches!(covers(&pat, &pats, 2), Outcome::Covered(Cover::Union(idxs)) if idxs == [0, 1]));
+    }
+
+    #[test]
+    fn rotated_far_end() {
+        let pats = patterns(&["A: (01)@  0  0@"]);
+        let pat: Pattern = "A: (10)@1  0  0@".parse().unwrap();
+        assert!(matches!(covers(&pat, &pats, 2), Outcome::Covered(Cover::Subsumed(0))));
+        let pat: Pattern = "A: (10)@  0  0@".parse().unwrap();
+        assert!(matches!(covers(&pat, &pats, 2), Outcome::NotMatched));
+    }
+
+    #[test]
+    fn undecided_above_subsets() {
+        // the 15th symbol from the end takes 2^15 subset states
+        let machine = Machine::parse("1RA---").unwrap();
+        let pats = patterns(&["A: 0@(.)*1..............  0  0@"]);
+        let report = verify(&machine, &pats).unwrap();
+        assert!(!report.closed);
+        assert!(matches!(&report.patterns[0].successors[0].outcome, Outcome::Undecided(idxs) if idxs == &[0]));
+        assert_eq!(report.undecided, 1);
+        assert!(report.unused.is_empty());
+        let printed = report.to_string();
+        assert!(printed.contains("??? UNDECIDED ??? (0)") && !printed.contains("NOT MATCHED"), "{}", printed);
+        assert!(printed.ends_with("NOT closed (1 undecided)"), "{}", printed);
+    }
+
+    #[test]
+    fn decide_halting() {
+        let machines = [
+            "1RB1LC_0LA1RB_0LD0RA_1RA---",
+            "1RB1LC_0LA1RB_1LD0RA_1RB---",
+            "1RB0LC_1LB1LC_1LD---_0RD0RA",
+            "1RB1LB_0LC0RA_1LD1LC_0RB---",
+        ];
+        for machine in machines {
+            let machine = Machine::parse(machine).unwrap();
+            for patterns in [10, 100] {
+                let result = decide(&machine, &Config { patterns, ..Default::default() });
+                assert!(!matches!(result, ProverResult::Infinite(_)), "{}: {}", machine, result);
+            }
+        }
+    }
+
+    #[test]
+    fn decide_infinite() {
+        let machine = Machine::parse("1RB0LB_1LA0RA").unwrap();
+        assert!(matches!(decide(&machine, &Default::default()), ProverResult::Infinite(_)));
+    }
+
+    #[test]
+    fn tape_match_absorbs() {
+        assert!(tape_match(&tape("0@0"), &tape("0@")));
+        assert!(tape_match(&tape("0@0"), &tape("0@((11)+0+)*")));
+        // can_absorb
+        assert!(tape_match(&tape("0@((11)+0+)*0"), &tape("0@((11)+0+)*")));
+    }
+
+    #[test]
+    fn tape_match_rejects() {
+        assert!(!tape_match(&tape("0@1"), &tape("0@")));
+        assert!(!tape_match(&tape("0@1+"), &tape("0@1")));
+    }
+}
@@ -0,0 +1,199 @@
+// bbchallenge.org seed database: https://bbchallenge.org/method#format-of-the-database
+// 30 byte header followed by 30 byte records; each record is 5 states x 2 symbols x (write, move, goto)
+// with move 0 == R, 1 == L and goto 1..=5 == A..=E, 0 == undefined
+
+use anyhow::{Context, Result, bail, ensure};
+use memmap2::Mmap;
+use rayon::prelude::*;
+use std::{convert::TryInto, fs::File, io::Write, ops::Range, path::Path};
+
+use crate::machine::{Head, Machine, Transition};
+
+pub const RECORD: usize = 30;
+const STATES: u8 = 5;
+const SYMBOLS: u8 = 2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Header {
+    /// machines undecided because of the time limit; they come first
+    pub undecided_time: u32,
+    /// machines undecided because of the space limit
+    pub undecided_space: u32,
+    pub total: u32,
+    pub sorted: bool,
+}
+
+impl Header {
+    pub fn decode(record: &[u8]) -> Header {
+        let u32_at = |i: usize| u32::from_be_bytes(record[i..i + 4].try_into().unwrap());
+        Header { undecided_time: u32_at(0), undecided_space: u32_at(4), total: u32_at(8), sorted: record[12] == 1 }
+    }
+
+    pub fn encode(&self) -> [u8; RECORD] {
+        let mut record = [0; RECORD];
+        record[0..4].copy_from_slice(&self.undecided_time.to_be_bytes());
+        record[4..8].copy_from_slice(&self.undecided_space.to_be_bytes());
+        record[8..12].copy_from_slice(&self.total.to_be_bytes());
+        record[12] = self.sorted as u8;
+        record
+    }
+}
+
+pub fn decode(record: &[u8]) -> Result<Machine> {
+    ensure!(record.len() == RECORD, "record has {} bytes", record.len());
+    let mut machine = Machine::new(STATES, SYMBOLS);
+    for (idx, trans) in record.chunks(3).enumerate() {
+        let (state, symbol) = ((idx / 2) as u8, (idx % 2) as u8);
+        let trans = match *trans {
+            [_, _, 0] => Transition::undefined(),
+            [write @ 0..=1, orient @ 0..=1, goto @ 1..=STATES] => {
+                Transition { symbol: write, head: Head { state: goto - 1, orient: 1 - orient } }
+            }
+            _ => bail!("invalid transition {:?} of state {} symbol {}", trans, (state + b'A') as char, symbol),
+        };
+        machine.set_transition(symbol, state, trans);
+    }
+    Ok(machine)
+}
+
+/// machines with less than 5 states are padded with undefined transitions; halting transitions are stored as undefined
+pub fn encode(machine: &Machine) -> Result<[u8; RECORD]> {
+    ensure!(machine.states() <= STATES && machine.symbols() == SYMBOLS, "{} is not a bb(5) machine", machine);
+    let mut record = [0; RECORD];
+    for state in 0..machine.states() {
+        for symbol in 0..SYMBOLS {
+            if let Some(trans) = machine.get_transition(symbol, state).filter(|t| t.head.state < machine.states()) {
+                let idx = 3 * (2 * state as usize + symbol as usize);
+                record[idx..idx + 3].copy_from_slice(&[trans.symbol, 1 - trans.head.orient, trans.head.state + 1]);
+            }
+        }
+    }
+    Ok(record)
+}
+
+pub struct SeedDb {
+    mmap: Mmap,
+    header: Header,
+}
+
+impl SeedDb {
+    pub fn open(path: impl AsRef<Path>) -> Result<SeedDb> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("can't open {}", path.display()))?;
+        // the database is read-only; modifying it while mapped is UB
+        let mmap = unsafe { Mmap::map(&file)? };
+        ensure!(
+            mmap.len() >= RECORD && mmap.len() % RECORD == 0,
+            "{}: size {} is not a multiple of {}",
+            path.display(),
+            mmap.len(),
+            RECORD
+        );
+        let header = Header::decode(&mmap[..RECORD]);
+        Ok(SeedDb { mmap, header })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// number of machine records
+    pub fn len(&self) -> u32 {
+        (self.mmap.len() / RECORD - 1) as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn record(&self, idx: u32) -> Option<&[u8]> {
+        let start = (idx as usize + 1) * RECORD;
+        self.mmap.get(start..start + RECORD)
+    }
+
+    pub fn get(&self, idx: u32) -> Result<Machine> {
+        let record = self.record(idx).with_context(|| format!("index {} out of {}", idx, self.len()))?;
+        decode(record).with_context(|| format!("machine {}", idx))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Result<(u32, Machine)>> + '_ {
+        (0..self.len()).map(move |idx| self.get(idx).map(|m| (idx, m)))
+    }
+
+    pub fn par_iter(&self, range: Range<u32>) -> impl ParallelIterator<Item = Result<(u32, Machine)>> + '_ {
+        range.into_par_iter().map(move |idx| self.get(idx).map(|m| (idx, m)))
+    }
+}
+
+pub fn write<'a>(mut w: impl Write, header: &Header, machines: impl IntoIterator<Item = &'a Machine>) -> Result<()> {
+    w.write_all(&header.encode())?;
+    machines.into_iter().try_for_each(|machine| Ok(w.write_all(&encode(machine)?)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// BB(5) champion, its halting transition is stored as undefined
+    const CHAMPION: &str = "1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA";
+
+    fn temp_db(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("bbc_seed_db_{}_{}", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn record() {
+        let record = encode(&Machine::from("1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA")).unwrap();
+        assert_eq!(record[..6], [1, 0, 2, 1, 1, 3]);
+        assert_eq!(record[24..], [0, 0, 0, 0, 1, 1]);
+        assert_eq!(decode(&record).unwrap().to_string(), CHAMPION);
+        assert_eq!(encode(&decode(&record).unwrap()).unwrap(), record);
+
+        // smaller machines are padded
+        let record = encode(&Machine::from("1RB1LB_1LA1RZ")).unwrap();
+        assert_eq!(decode(&record).unwrap().to_string(), "1RB1LB_1LA---_------_------_------");
+
+        assert!(decode(&record[1..]).is_err());
+        assert!(decode(&[[1, 2, 3]; 10].concat()).is_err());
+        assert!(encode(&Machine::from("1RB2LA1RA1RA_1LB1LA3RB1RZ")).is_err());
+    }
+
+    #[test]
+    fn header() {
+        let header =
+            Header { undecided_time: 14_322_029, undecided_space: 74_342_035, total: 88_664_064, sorted: true };
+        let record = header.encode();
+        assert_eq!(record[..13], [0x00, 0xda, 0x89, 0x6d, 0x04, 0x6e, 0x5e, 0x93, 0x05, 0x48, 0xe8, 0x00, 1]);
+        assert_eq!(Header::decode(&record), header);
+    }
+
+    #[test]
+    fn database() {
+        let header = Header { undecided_time: 1, undecided_space: 1, total: 2, sorted: false };
+        let machines = [Machine::from(CHAMPION), Machine::from("1RB1LB_1LA---_------_------_------")];
+        let mut bytes = vec![];
+        write(&mut bytes, &header, &machines).unwrap();
+        assert_eq!(bytes.len(), 3 * RECORD);
+
+        let path = temp_db("database", &bytes);
+        let db = SeedDb::open(&path).unwrap();
+        assert_eq!((*db.header(), db.len()), (header, 2));
+        assert_eq!(db.get(0).unwrap().to_string(), CHAMPION);
+        let all: Vec<String> = db.iter().map(|m| m.unwrap().1.to_string()).collect();
+        assert_eq!(all, machines.iter().map(Machine::to_string).collect::<Vec<_>>());
+        let par: Vec<u32> = db.par_iter(0..2).map(|m| m.unwrap().0).collect();
+        assert_eq!(par, [0, 1]);
+        assert!(db.record(2).is_none());
+        assert_eq!(db.get(2).err().unwrap().to_string(), "index 2 out of 2");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn truncated_database() {
+        let path = temp_db("truncated", &[0; RECORD + 1]);
+        assert!(SeedDb::open(&path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,220 @@
+use crate::{
+    ProverResult,
+    machine::{Machine, Orientation, Transition},
+    pipeline,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    pub steps: u64,
+    /// max number of visited cells
+    pub space: Option<usize>,
+}
+
+impl Limits {
+    pub fn steps(steps: u64) -> Limits {
+        Limits { steps, space: None }
+    }
+}
+
+/// direct simulation on a bidirectional tape; undefined transition is executed as `1RZ`
+#[derive(Clone)]
+pub struct Simulator<'a> {
+    machine: &'a Machine,
+    tape: Vec<u8>,
+    pos: usize,
+    /// tape index of the starting cell
+    origin: usize,
+    /// visited cells are `tape[lo..=hi]`
+    lo: usize,
+    hi: usize,
+    state: u8,
+    /// direction of the last move
+    orient: Orientation,
+    steps: u64,
+    /// `(state, symbol)` of the halting transition
+    halted: Option<(u8, u8)>,
+}
+
+impl<'a> Simulator<'a> {
+    pub fn new(machine: &'a Machine) -> Simulator<'a> {
+        const INITIAL: usize = 64;
+        Simulator {
+            machine,
+            tape: vec![0; 2 * INITIAL],
+            pos: INITIAL,
+            origin: INITIAL,
+            lo: INITIAL,
+            hi: INITIAL,
+            state: 0,
+            orient: 1,
+            steps: 0,
+            halted: None,
+        }
+    }
+
+    /// @returns `false` if the machine is halted (by this or some previous step)
+    pub fn step(&mut self) -> bool {
+        if self.halted.is_some() {
+            return false;
+        }
+        let symbol = self.tape[self.pos];
+        let trans = self.machine.get_transition(symbol, self.state).unwrap_or_else(Transition::last);
+        if trans.head.state >= self.machine.states() {
+            self.halted = Some((self.state, symbol));
+        }
+
+        self.tape[self.pos] = trans.symbol;
+        if trans.head.orient == 0 {
+            if self.pos == 0 {
+                self.grow_left();
+            }
+            self.pos -= 1;
+            self.lo = self.lo.min(self.pos);
+        } else {
+            if self.pos + 1 == self.tape.len() {
+                self.tape.resize(2 * self.tape.len(), 0);
+            }
+            self.pos += 1;
+            self.hi = self.hi.max(self.pos);
+        }
+        self.state = trans.head.state;
+        self.orient = trans.head.orient;
+        self.steps += 1;
+
+        self.halted.is_none()
+    }
+
+    fn grow_left(&mut self) {
+        let ext = self.tape.len();
+        let mut tape = vec![0; 2 * ext];
+        tape[ext..].copy_from_slice(&self.tape);
+        self.tape = tape;
+        self.pos += ext;
+        self.origin += ext;
+        self.lo += ext;
+        self.hi += ext;
+    }
+
+    /// `Limit` if the simulation can't continue within `limits`
+    pub fn check(&self, limits: Limits) -> Option<ProverResult> {
+        if self.steps >= limits.steps {
+            return Some(ProverResult::Limit(format!("steps: {}", limits.steps)));
+        }
+        if self.steps & 0xfff == 0 && pipeline::expired() {
+            return Some(ProverResult::Limit("time".to_owned()));
+        }
+        limits.space.filter(|&space| self.space() > space).map(|space| ProverResult::Limit(format!("space: {}", space)))
+    }
+
+    pub fn run(&mut self, limits: Limits) -> ProverResult {
+        while self.halted.is_none() {
+            if let Some(limit) = self.check(limits) {
+                return limit;
+            }
+            self.step();
+        }
+        self.result()
+    }
+
+    /// `Halt` once halted
+    pub fn result(&self) -> ProverResult {
+        ProverResult::Halt { steps: self.steps, sigma: self.sigma() }
+    }
+
+    pub fn machine(&self) -> &'a Machine {
+        self.machine
+    }
+
+    #[inline(always)]
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// direction of the last move; `1` before the first step
+    #[inline(always)]
+    pub fn orient(&self) -> Orientation {
+        self.orient
+    }
+
+    #[inline(always)]
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// symbol under the head
+    #[inline(always)]
+    pub fn symbol(&self) -> u8 {
+        self.tape[self.pos]
+    }
+
+    /// head position relative to the starting cell
+    pub fn position(&self) -> i64 {
+        self.pos as i64 - self.origin as i64
+    }
+
+    /// `(state, symbol)` of the transition that halted the machine
+    pub fn halted(&self) -> Option<(u8, u8)> {
+        self.halted
+    }
+
+    /// visited cells
+    pub fn tape(&self) -> &[u8] {
+        &self.tape[self.lo..=self.hi]
+    }
+
+    /// head index into `tape()`
+    pub fn head(&self) -> usize {
+        self.pos - self.lo
+    }
+
+    /// number of visited cells
+    pub fn space(&self) -> usize {
+        self.hi - self.lo + 1
+    }
+
+    /// number of non-zero symbols on the tape
+    pub fn sigma(&self) -> u64 {
+        self.tape().iter().filter(|&&s| s != 0).count() as u64
+    }
+}
+
+pub fn simulate(machine: &Machine, limits: Limits) -> ProverResult {
+    Simulator::new(machine).run(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn busy_beavers() {
+        for (machine, steps, sigma) in
+            [("1RB1LB_1LA1RZ", 6, 4), ("1RB1RZ_1LB0RC_1LC1LA", 21, 5), ("1RB1LB_1LA0LC_1RZ1LD_1RD0RA", 107, 13)]
+        {
+            let result = simulate(&Machine::from(machine), Limits::steps(1000));
+            assert_eq!(result.to_string(), ProverResult::Halt { steps, sigma }.to_string(), "{}", machine);
+        }
+    }
+
+    #[test]
+    fn step_limit() {
+        let result = simulate(&Machine::from("1RB1LB_1LA0LC_1RZ1LD_1RD0RA"), Limits::steps(106));
+        assert_eq!(result.to_string(), "Limit(\"steps: 106\")");
+    }
+
+    #[test]
+    fn space_limit() {
+        let result = simulate(&Machine::from("1RA---"), Limits { steps: 1000, space: Some(10) });
+        assert_eq!(result.to_string(), "Limit(\"space: 10\")");
+    }
+
+    #[test]
+    fn grows_left() {
+        let machine = Machine::from("1LA---");
+        let mut sim = Simulator::new(&machine);
+        assert_eq!(sim.run(Limits::steps(200)).to_string(), "Limit(\"steps: 200\")");
+        assert_eq!((sim.position(), sim.head(), sim.space(), sim.sigma()), (-200, 0, 201, 200));
+        assert_eq!(sim.tape()[200], 1);
+    }
+}
@@ -0,0 +1,165 @@
+// closed position set (skelet's cps): the tape is cut into aligned segments of length `len`; a position is
+// (segment under the head, offset in it, head) and the rest of the tape is abstracted into the sets of segments
+// seen to the left & to the right of the head
+// leaving the segment pushes it into the set on that side and enters any segment from the other set;
+// if the positions & both sets close without reaching a halting transition, the machine never halts
+
+use hashbrown::HashSet;
+
+use crate::{
+    ProverResult,
+    interner::{ITape, InternerTape},
+    machine::{Head, Machine},
+    pipeline,
+};
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// segment lengths to try, in order
+    pub lengths: Vec<usize>,
+    /// max number of positions for one segment length
+    pub positions: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { lengths: (1..=8).collect(), positions: 100_000 }
+    }
+}
+
+type Position = (ITape, usize, Head);
+
+struct Cps<'a> {
+    machine: &'a Machine,
+    len: usize,
+    interner: InternerTape<u8>,
+    positions: HashSet<Position>,
+    queue: Vec<Position>,
+    /// `segments[orient]`: segments left (0) & right (1) of the head
+    segments: [Vec<ITape>; 2],
+    /// `exits[orient]`: states in which the head left its segment to the left (0) & right (1)
+    exits: [Vec<u8>; 2],
+}
+
+impl<'a> Cps<'a> {
+    fn push(&mut self, position: Position) {
+        if self.positions.insert(position) {
+            self.queue.push(position);
+        }
+    }
+
+    /// head in `state` enters some segment from side `orient`
+    fn exit(&mut self, state: u8, orient: usize) {
+        if self.exits[orient].contains(&state) {
+            return;
+        }
+        self.exits[orient].push(state);
+        let offset = if orient == 1 { 0 } else { self.len - 1 };
+        for seg in self.segments[orient].clone() {
+            self.push((seg, offset, Head { state, orient: orient as u8 }));
+        }
+    }
+
+    fn add_segment(&mut self, seg: ITape, side: usize) {
+        if self.segments[side].contains(&seg) {
+            return;
+        }
+        self.segments[side].push(seg);
+        let offset = if side == 1 { 0 } else { self.len - 1 };
+        for state in self.exits[side].clone() {
+            self.push((seg, offset, Head { state, orient: side as u8 }));
+        }
+    }
+
+    /// @returns `Some` if the run can't be closed
+    fn step(&mut self, (seg, offset, head): Position) -> Option<ProverResult> {
+        let mut tape = self.interner[seg].to_vec();
+        let trans = match self.machine.get_transition(tape[offset], head.state) {
+            Some(trans) if trans.head.state < self.machine.states() => trans,
+            _ => {
+                let symbol = tape[offset];
+                return Some(ProverResult::Limit(format!(
+                    "halting {}{} reachable",
+                    (head.state + b'A') as char,
+                    symbol
+                )));
+            }
+        };
+        tape[offset] = trans.symbol;
+        let seg = self.interner.get_or_insert(&tape);
+        match (trans.head.orient, offset) {
+            (0, 0) => {
+                self.add_segment(seg, 1);
+                self.exit(trans.head.state, 0);
+            }
+            (1, offset) if offset + 1 == self.len => {
+                self.add_segment(seg, 0);
+                self.exit(trans.head.state, 1);
+            }
+            (0, offset) => self.push((seg, offset - 1, trans.head)),
+            (_, offset) => self.push((seg, offset + 1, trans.head)),
+        }
+        None
+    }
+}
+
+fn decide_len(machine: &Machine, len: usize, max_positions: usize) -> ProverResult {
+    let mut interner = InternerTape::new();
+    let zero = interner.get_or_insert(&vec![0; len]);
+    let mut cps = Cps {
+        machine,
+        len,
+        interner,
+        positions: HashSet::new(),
+        queue: vec![],
+        segments: [vec![zero], vec![zero]],
+        exits: [vec![], vec![]],
+    };
+    cps.push((zero, 0, Head { state: 0, orient: 1 }));
+    while let Some(position) = cps.queue.pop() {
+        if cps.positions.len() > max_positions {
+            return ProverResult::Limit(format!("positions: {}", max_positions));
+        }
+        if pipeline::expired() {
+            return ProverResult::Limit("time".to_owned());
+        }
+        if let Some(result) = cps.step(position) {
+            return result;
+        }
+    }
+    ProverResult::Infinite(format!("skelet cps: segment {}, positions {}", len, cps.positions.len()))
+}
+
+pub fn decide(machine: &Machine, config: &Config) -> ProverResult {
+    ProverResult::first_infinite(config.lengths.iter().copied(), "no segment length", |len| {
+        decide_len(machine, len, config.positions)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_and_positions() {
+        for (machine, summary) in [
+            ("1RB0LB_0LA---", "skelet cps: segment 2, positions 5"),
+            ("1RB0RC_1RC---_1LC0RA", "skelet cps: segment 5, positions 18"),
+        ] {
+            let result = decide(&Machine::from(machine), &Config::default());
+            assert_eq!(result.to_string(), format!("Infinite(\"{}\")", summary), "{}", machine);
+        }
+    }
+
+    #[test]
+    fn halting_reachable() {
+        let result = decide(&Machine::from("1RB1LC_0LA1RB_0LD0RA_1RA---"), &Config::default());
+        assert_eq!(result.to_string(), "Limit(\"halting D1 reachable\")");
+    }
+
+    #[test]
+    fn no_lengths() {
+        let result = decide(&Machine::from("1RB0LB_0LA---"), &Config { lengths: vec![], ..Config::default() });
+        assert_eq!(result.to_string(), "Limit(\"no segment length\")");
+    }
+}
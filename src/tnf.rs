@@ -0,0 +1,119 @@
+// tree normal form enumeration: simulate until an undefined transition is hit, then define it in all canonical ways
+// (states & written symbols are introduced in order, so machines differing only by their names aren't repeated)
+
+use rayon::prelude::*;
+
+use crate::{
+    ProverResult,
+    machine::{Head, Machine, Transition},
+    simulator::{Limits, Simulator},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// at least 2
+    pub states: u8,
+    pub symbols: u8,
+    pub limits: Limits,
+}
+
+/// result of the simulation & children; `Halt` means halting on the undefined transition
+fn expand(machine: &Machine, config: &Config) -> (ProverResult, Vec<Machine>) {
+    let mut sim = Simulator::new(machine);
+    let result = sim.run(config.limits);
+    let Some((state, symbol)) = sim.halted() else {
+        return (result, vec![]);
+    };
+
+    let transitions = (0..machine.states()).flat_map(|state| (0..machine.symbols()).map(move |symbol| (symbol, state)));
+    let (mut undefined, mut max_state, mut max_symbol) = (0, 0, 0);
+    for (s, q) in transitions {
+        match machine.get_transition(s, q) {
+            Some(t) => {
+                max_state = max_state.max(q).max(t.head.state);
+                max_symbol = max_symbol.max(t.symbol);
+            }
+            None => undefined += 1,
+        }
+    }
+    // the last undefined transition is the halting one
+    if undefined == 1 {
+        return (result, vec![]);
+    }
+
+    let max_state = (max_state + 1).min(config.states - 1);
+    let max_symbol = (max_symbol + 1).min(config.symbols - 1);
+    let children = (0..=max_state)
+        .flat_map(|next| (0..=max_symbol).flat_map(move |write| (0..=1).map(move |orient| (next, write, orient))))
+        .map(|(next, write, orient)| {
+            let mut child = machine.clone();
+            child.set_transition(symbol, state, Transition { symbol: write, head: Head { state: next, orient } });
+            child
+        })
+        .collect();
+    (result, children)
+}
+
+fn root(config: &Config) -> Machine {
+    let mut machine = Machine::new(config.states, config.symbols);
+    machine.set_transition(0, 0, Transition::first());
+    machine
+}
+
+/// calls `f` for every machine which halts (on its first undefined transition) or hits the limits
+pub fn enumerate(config: &Config, mut f: impl FnMut(&Machine, &ProverResult)) {
+    let mut stack = vec![root(config)];
+    while let Some(machine) = stack.pop() {
+        let (result, children) = expand(&machine, config);
+        f(&machine, &result);
+        stack.extend(children.into_iter().rev());
+    }
+}
+
+pub fn par_enumerate(config: &Config, f: impl Fn(&Machine, &ProverResult) + Sync) {
+    fn rec(machine: Machine, config: &Config, f: &(impl Fn(&Machine, &ProverResult) + Sync)) {
+        let (result, children) = expand(&machine, config);
+        f(&machine, &result);
+        children.into_par_iter().for_each(|child| rec(child, config, f));
+    }
+    rec(root(config), config, &f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (enumerated machines, halting ones, max halting steps)
+    fn count(states: u8, symbols: u8) -> (usize, usize, u64) {
+        let config = Config { states, symbols, limits: Limits::steps(1000) };
+        let (mut machines, mut halting, mut max_steps) = (0, 0, 0);
+        enumerate(&config, |_, result| {
+            machines += 1;
+            if let &ProverResult::Halt { steps, .. } = result {
+                halting += 1;
+                max_steps = max_steps.max(steps);
+            }
+        });
+        (machines, halting, max_steps)
+    }
+
+    #[test]
+    fn busy_beavers() {
+        assert_eq!(count(2, 2), (41, 15, 6));
+        assert_eq!(count(3, 2), (4057, 1379, 21));
+        assert_eq!(count(2, 3), (2833, 866, 38));
+    }
+
+    #[test]
+    fn parallel() {
+        let config = Config { states: 3, symbols: 2, limits: Limits::steps(1000) };
+        let mut machines = vec![];
+        enumerate(&config, |machine, _| machines.push(machine.to_string()));
+        let par = std::sync::Mutex::new(vec![]);
+        par_enumerate(&config, |machine, _| par.lock().unwrap().push(machine.to_string()));
+        let mut par = par.into_inner().unwrap();
+        machines.sort_unstable();
+        par.sort_unstable();
+        assert_eq!(machines, par);
+    }
+}
@@ -0,0 +1,127 @@
+// translated cyclers: the machine repeats a configuration shifted along the tape
+// a record is a step on which the head visits a new rightmost cell; if two records `r1`, `r2` are in the same
+// state and the head never went further back than `m` in between, the run from `r1` to `r2` only depends on
+// the cells `m..=r1` (everything to the right is blank); so when `r2` has the same window behind the head,
+// the run repeats forever, each time shifted by `r2 - r1`
+// a machine drifting left never makes a right edge record, so after a limit the mirrored machine is run too
+
+use std::borrow::Cow;
+
+use crate::{
+    ProverResult,
+    interner::{ITape, InternerTape, LEN_MAX},
+    machine::Machine,
+    simulator::{Limits, Simulator},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// `space` is lowered to `LEN_MAX` since each record interns the tape up to the head
+    pub limits: Limits,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { limits: Limits { steps: 10_000, space: Some(1000) } }
+    }
+}
+
+struct Record {
+    state: u8,
+    steps: u64,
+    position: i64,
+    /// visited tape up to & including the head
+    tape: ITape,
+    /// leftmost position of the head since this record
+    min: i64,
+}
+
+/// `w` last cells of `tape`; cells left of the visited tape are blank
+fn window(tape: &[u8], w: usize) -> Cow<'_, [u8]> {
+    match tape.len().checked_sub(w) {
+        Some(start) => Cow::Borrowed(&tape[start..]),
+        None => Cow::Owned([&vec![0; w - tape.len()][..], tape].concat()),
+    }
+}
+
+fn decide_right(machine: &Machine, limits: Limits, mirrored: bool) -> ProverResult {
+    let mut sim = Simulator::new(machine);
+    let mut interner = InternerTape::new();
+    let mut records: Vec<Record> = Vec::new();
+    let mut max = i64::MIN;
+    loop {
+        // interned tapes are at most `LEN_MAX` long
+        if let Some(limit) = sim.check(limits) {
+            return limit;
+        }
+        let position = sim.position();
+        if position > max {
+            max = position;
+            let tape = &sim.tape()[..=sim.head()];
+            for r in records.iter().rev().filter(|r| r.state == sim.state()) {
+                let window_len = (r.position - r.min + 1) as usize;
+                let then = window(&interner[r.tape], window_len).into_owned();
+                let then = interner.get_or_insert(&then);
+                if interner.get(&window(tape, window_len)) == Some(then) {
+                    let shift = position - r.position;
+                    return ProverResult::Infinite(format!(
+                        "translated cycler: shift {}, period {}, window {}, preperiod {}",
+                        if mirrored { -shift } else { shift },
+                        sim.steps() - r.steps,
+                        window_len,
+                        r.steps
+                    ));
+                }
+            }
+            let tape = interner.get_or_insert(tape);
+            records.push(Record { state: sim.state(), steps: sim.steps(), position, tape, min: position });
+        }
+
+        if !sim.step() {
+            return sim.result();
+        }
+        // `min` is nondecreasing along `records`
+        let position = sim.position();
+        for r in records.iter_mut().rev().take_while(|r| r.min > position) {
+            r.min = position;
+        }
+    }
+}
+
+pub fn decide(machine: &Machine, config: &Config) -> ProverResult {
+    let limits =
+        Limits { space: Some(config.limits.space.map_or(LEN_MAX, |space| space.min(LEN_MAX))), ..config.limits };
+    match decide_right(machine, limits, false) {
+        ProverResult::Limit(_) => decide_right(&machine.mirror(), limits, true),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shift_and_window() {
+        for (machine, summary) in [
+            ("1RB0RA_0LA---", "translated cycler: shift 1, period 3, window 2, preperiod 1"),
+            ("1RB0RC_1LC---_0RA0LB", "translated cycler: shift 3, period 9, window 3, preperiod 0"),
+        ] {
+            let result = decide(&Machine::from(machine), &Config::default());
+            assert_eq!(result.to_string(), format!("Infinite(\"{}\")", summary), "{}", machine);
+        }
+    }
+
+    #[test]
+    fn left_edge() {
+        let result = decide(&Machine::from("1RB0LA_0LA---"), &Config::default());
+        assert_eq!(result.to_string(), "Infinite(\"translated cycler: shift -1, period 3, window 2, preperiod 0\")");
+    }
+
+    #[test]
+    fn halts() {
+        // BB(3) champion
+        let result = decide(&Machine::from("1RB1RZ_1LB0RC_1LC1LA"), &Config::default());
+        assert_eq!(result.to_string(), "Halt(21, 5)");
+    }
+}